@@ -0,0 +1,151 @@
+//! A component which resolves the outer future when it is cancelled.
+//!
+//! Rather than adding a shutdown channel to every future and a component to
+//! poll it, a [`Cancellable`] component can be added to the list of
+//! components. It watches a [`Token`] stored on the outer future or stream,
+//! and once the token has been cancelled, it calls the given handler to
+//! decide which value or error the outer future or stream should resolve
+//! with. Since components are polled in order, it should usually be the
+//! first component in the list, so that no other work is done once the
+//! token has been cancelled.
+//!
+//! # Examples
+//!
+//! ```
+//! use component_future::cancel::{Cancellable, Token};
+//!
+//! struct Worker {
+//!     cancel: Token,
+//!     // ...
+//! }
+//!
+//! impl Worker {
+//!     const POLL_FNS: &'static [&'static dyn component_future::Component<
+//!         Self,
+//!         u32,
+//!         String,
+//!     >] = &[
+//!         &Cancellable::new(
+//!             |worker: &mut Self| &mut worker.cancel,
+//!             &|_| Err("cancelled".to_string()),
+//!         ),
+//!         &Self::poll_work,
+//!     ];
+//!
+//!     fn poll_work(&mut self) -> component_future::Poll<u32, String> {
+//!         // ...
+//! #       Ok(component_future::Async::NothingToDo)
+//!     }
+//! }
+//!
+//! impl futures::future::Future for Worker {
+//!     type Item = u32;
+//!     type Error = String;
+//!
+//!     fn poll(&mut self) -> futures::Poll<Self::Item, Self::Error> {
+//!         component_future::poll_future(self, Self::POLL_FNS)
+//!     }
+//! }
+//! ```
+
+use crate::{Async, Component, Poll};
+
+use futures::future::Future as _;
+use std::sync::{Arc, Mutex};
+
+/// A cancellation token which can be shared between any number of futures
+/// and streams.
+///
+/// Clones of a token share the same state, so cancelling any clone cancels
+/// all of them, and wakes up every task which is waiting on one. The token
+/// is built on a `futures::sync::oneshot` channel, and each clone only
+/// keeps track of the task which most recently polled it (which is
+/// forgotten when the clone is dropped), so every future or stream should
+/// have its own clone.
+#[derive(Debug, Clone)]
+pub struct Token {
+    sender: Arc<Mutex<Option<futures::sync::oneshot::Sender<()>>>>,
+    cancelled: futures::future::Shared<futures::sync::oneshot::Receiver<()>>,
+}
+
+impl Token {
+    /// Creates a token which hasn't been cancelled.
+    #[must_use]
+    pub fn new() -> Self {
+        let (sender, receiver) = futures::sync::oneshot::channel();
+        Self {
+            sender: Arc::new(Mutex::new(Some(sender))),
+            cancelled: receiver.shared(),
+        }
+    }
+
+    /// Cancels every future and stream watching this token.
+    ///
+    /// # Panics
+    ///
+    /// Panics if another thread panicked while cancelling the token.
+    pub fn cancel(&self) {
+        let sender = self.sender.lock().unwrap().take();
+        if let Some(sender) = sender {
+            // every token holds on to the receiver, so it can't have been
+            // dropped yet
+            let _ = sender.send(());
+        }
+    }
+
+    /// Returns true if [`cancel`](Self::cancel) has been called.
+    ///
+    /// # Panics
+    ///
+    /// Panics if another thread panicked while cancelling the token.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.sender.lock().unwrap().is_none()
+    }
+
+    // returns whether the token has been cancelled, and if not, arranges for
+    // the current task to be notified when it is
+    fn poll_cancelled(&mut self) -> bool {
+        !matches!(self.cancelled.poll(), Ok(futures::Async::NotReady))
+    }
+}
+
+impl Default for Token {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A component which calls a handler to resolve the outer future or stream
+/// once its [`Token`] has been cancelled.
+pub struct Cancellable<'a, T, Item, Error> {
+    token: fn(&mut T) -> &mut Token,
+    handler: &'a dyn Fn(&mut T) -> Result<Item, Error>,
+}
+
+impl<'a, T, Item, Error> Cancellable<'a, T, Item, Error> {
+    /// Creates a component which watches the [`Token`] returned by `token`.
+    /// Once it has been cancelled, the outer future or stream resolves with
+    /// the value or error returned by `on_cancel`.
+    pub const fn new(
+        token: fn(&mut T) -> &mut Token,
+        on_cancel: &'a dyn Fn(&mut T) -> Result<Item, Error>,
+    ) -> Self {
+        Self {
+            token,
+            handler: on_cancel,
+        }
+    }
+}
+
+impl<T, Item, Error> Component<T, Item, Error>
+    for Cancellable<'_, T, Item, Error>
+{
+    fn poll(&self, target: &mut T) -> Poll<Item, Error> {
+        if (self.token)(target).poll_cancelled() {
+            (self.handler)(target).map(Async::Ready)
+        } else {
+            Ok(Async::NotReady)
+        }
+    }
+}
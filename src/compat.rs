@@ -0,0 +1,126 @@
+//! Adapters between components written for `futures` 0.1 and components
+//! written for `core::task`.
+//!
+//! [`Compat01`] allows a component which polls `futures` 0.1 futures (and so
+//! needs to run inside a `futures` 0.1 task) to be used with the drivers in
+//! the [`task`](../task/index.html) module, and [`Compat03`] allows a
+//! component which needs a `core::task::Context` to be used with
+//! [`poll_future`](../fn.poll_future.html) and
+//! [`poll_stream`](../fn.poll_stream.html). Both are built on
+//! `futures::compat`, so wakeups registered by the inner futures are
+//! forwarded to the outer task.
+//!
+//! This module is only available with the `compat` feature.
+
+use crate::{Async, Component, Poll};
+
+use futures03::compat::Future01CompatExt as _;
+use futures03::future::{FutureExt as _, TryFutureExt as _};
+
+/// Wraps a `futures` 0.1 style component so that it can be used as a
+/// [`task::Component`](../task/trait.Component.html).
+pub struct Compat01<C>(pub C);
+
+impl<T, Item, Error, C> crate::task::Component<T, Item, Error> for Compat01<C>
+where
+    C: Component<T, Item, Error>,
+{
+    fn poll(
+        &self,
+        target: &mut T,
+        cx: &mut core::task::Context<'_>,
+    ) -> Poll<Item, Error> {
+        let fut = futures::future::poll_fn(|| {
+            let res = self.0.poll(target);
+            if matches!(res, Ok(Async::NotReady)) {
+                Ok(futures::Async::NotReady)
+            } else {
+                Ok::<_, core::convert::Infallible>(futures::Async::Ready(res))
+            }
+        });
+        match fut.compat().poll_unpin(cx) {
+            core::task::Poll::Ready(Ok(res)) => res,
+            core::task::Poll::Ready(Err(e)) => match e {},
+            core::task::Poll::Pending => Ok(Async::NotReady),
+        }
+    }
+}
+
+/// Wraps a [`task::Component`](../task/trait.Component.html) so that it can
+/// be used as a `futures` 0.1 style [`Component`].
+pub struct Compat03<C>(pub C);
+
+impl<T, Item, Error, C> Component<T, Item, Error> for Compat03<C>
+where
+    C: crate::task::Component<T, Item, Error>,
+{
+    fn poll(&self, target: &mut T) -> Poll<Item, Error> {
+        let fut = futures03::future::poll_fn(|cx| {
+            let res = self.0.poll(target, cx);
+            if matches!(res, Ok(Async::NotReady)) {
+                core::task::Poll::Pending
+            } else {
+                core::task::Poll::Ready(Ok::<_, core::convert::Infallible>(
+                    res,
+                ))
+            }
+        });
+        match futures::future::Future::poll(&mut fut.compat()) {
+            Ok(futures::Async::Ready(res)) => res,
+            Ok(futures::Async::NotReady) => Ok(Async::NotReady),
+            Err(e) => match e {},
+        }
+    }
+}
+
+/// A value which can be passed to
+/// [`try_ready_compat!`](../macro.try_ready_compat.html).
+///
+/// This is implemented for both `futures::Poll<T, E>` and
+/// `core::task::Poll<Result<T, E>>`, so that components which poll a mix of
+/// `futures` 0.1 and `core::task` futures can use the same macro for both.
+pub trait TryReady<T, E> {
+    /// Returns `Ok(Some(t))` if the value is ready, `Ok(None)` if it isn't,
+    /// and `Err(e)` if it failed.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error contained in `self`, if any.
+    fn try_ready(self) -> Result<Option<T>, E>;
+}
+
+impl<T, E> TryReady<T, E> for futures::Poll<T, E> {
+    fn try_ready(self) -> Result<Option<T>, E> {
+        match self? {
+            futures::Async::Ready(t) => Ok(Some(t)),
+            futures::Async::NotReady => Ok(None),
+        }
+    }
+}
+
+impl<T, E> TryReady<T, E> for core::task::Poll<Result<T, E>> {
+    fn try_ready(self) -> Result<Option<T>, E> {
+        match self {
+            Self::Ready(res) => res.map(Some),
+            Self::Pending => Ok(None),
+        }
+    }
+}
+
+/// A macro for extracting the successful type of either a
+/// `futures::Poll<T, E>` or a `core::task::Poll<Result<T, E>>` and turning
+/// it into a `component_future::Poll<T, E>`.
+///
+/// This behaves like [`try_ready!`](macro.try_ready.html) or
+/// [`try_ready_task!`](macro.try_ready_task.html), depending on the type of
+/// its argument. It is only available with the `compat` feature.
+#[macro_export]
+macro_rules! try_ready_compat {
+    ($e:expr) => {
+        match $crate::compat::TryReady::try_ready($e) {
+            Ok(Some(t)) => t,
+            Ok(None) => return Ok($crate::Async::NotReady),
+            Err(e) => return Err(From::from(e)),
+        }
+    };
+}
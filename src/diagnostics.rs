@@ -0,0 +1,144 @@
+//! Reports about futures and streams which are failing to make progress.
+
+/// A report of a future or stream whose components are unable to make
+/// progress.
+///
+/// These are passed to the handler set with
+/// [`Driver::on_stall`](../struct.Driver.html#method.on_stall).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Stall {
+    /// The component at index `component` returned `Async::DidWork` for
+    /// `iterations` consecutive passes without any component returning a
+    /// value, which likely means that it is claiming to have done work
+    /// without actually changing any state.
+    Livelock {
+        /// The index of the component in the list of components.
+        component: usize,
+
+        /// The number of consecutive passes for which the component
+        /// returned `Async::DidWork`.
+        iterations: usize,
+    },
+}
+
+impl std::fmt::Display for Stall {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Livelock {
+                component,
+                iterations,
+            } => write!(
+                f,
+                "component {component} returned DidWork for {iterations} \
+                 consecutive iterations without producing a value"
+            ),
+        }
+    }
+}
+
+/// A report of a future or stream which passed its deadline without
+/// producing a value.
+///
+/// These are passed to the error constructor given to
+/// [`timeout::Deadline`](../timeout/struct.Deadline.html).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expired {
+    pub(crate) pending: Vec<usize>,
+}
+
+impl Expired {
+    /// Returns the indexes of the components which returned
+    /// `Async::NotReady` or `Async::Blocked` during the final poll before the
+    /// deadline expired.
+    #[must_use]
+    pub fn pending(&self) -> &[usize] {
+        &self.pending
+    }
+}
+
+impl std::fmt::Display for Expired {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "deadline expired while components {:?} were still pending",
+            self.pending
+        )
+    }
+}
+
+/// A report of a future or stream which is waiting on full downstream sinks.
+///
+/// These are passed to the handler set with
+/// [`Driver::on_backpressure`](../struct.Driver.html#method.on_backpressure)
+/// whenever the outer future or stream returns `NotReady` while at least one
+/// of its components returned `Async::Blocked`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backpressure {
+    pub(crate) blocked: Vec<usize>,
+}
+
+impl Backpressure {
+    /// Returns the indexes of the components which returned
+    /// `Async::Blocked` during the final pass before the outer future or
+    /// stream returned `NotReady`.
+    #[must_use]
+    pub fn blocked(&self) -> &[usize] {
+        &self.blocked
+    }
+}
+
+impl std::fmt::Display for Backpressure {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "components {:?} are blocked on full downstream sinks",
+            self.blocked
+        )
+    }
+}
+
+/// A report of a future or stream whose components all returned
+/// `Async::NothingToDo` during the same pass, which is a bug in the
+/// components.
+///
+/// The drivers which take a guard for each component
+/// ([`poll_future_guarded`](../fn.poll_future_guarded.html) and
+/// [`poll_stream_guarded`](../fn.poll_stream_guarded.html)) panic with this
+/// report, so that components which were skipped because their guard
+/// returned false can be told apart from components which were polled and
+/// had nothing to do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Idle {
+    pub(crate) disabled: Vec<usize>,
+}
+
+impl Idle {
+    /// Returns the indexes of the components which were disabled by their
+    /// guards. All other components were polled, and returned
+    /// `Async::NothingToDo`.
+    #[must_use]
+    pub fn disabled(&self) -> &[usize] {
+        &self.disabled
+    }
+}
+
+impl std::fmt::Display for Idle {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "all components returned NothingToDo (components {:?} were \
+             disabled by their guards)",
+            self.disabled
+        )
+    }
+}
+
+/// The default stall handler, which panics with a description of the
+/// stall.
+///
+/// # Panics
+///
+/// Always.
+pub fn panic_on_stall(stall: &Stall) {
+    panic!("{}", stall)
+}
@@ -0,0 +1,881 @@
+use crate::diagnostics::{Backpressure, Idle, Stall};
+use crate::mask::Mask;
+use crate::phases::Phases;
+use crate::queue::Queue;
+use crate::shutdown::{Role, Signal};
+use crate::timeout::{Deadline, Timer};
+use crate::{Async, Component, ComponentWith, Poll};
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Configurable version of [`poll_future`](fn.poll_future.html) and
+/// [`poll_stream`](fn.poll_stream.html).
+///
+/// `poll_future(self, Self::POLL_FNS)` is equivalent to
+/// `Driver::new().poll_future(self, Self::POLL_FNS)`. The methods on this
+/// type can be used to enable additional checks and behaviors.
+///
+/// # Examples
+///
+/// ```
+/// # use futures::future::Future;
+/// # struct Foo;
+/// # impl Foo {
+/// #     const POLL_FNS:
+/// #         &'static [&'static dyn for<'a> Fn(
+/// #             &'a mut Self,
+/// #         ) -> component_future::Poll<(), ()>] = &[];
+/// # }
+/// impl Foo {
+///     const DRIVER: component_future::Driver =
+///         component_future::Driver::new()
+///             .check_wakeups(cfg!(debug_assertions));
+/// }
+///
+/// impl Future for Foo {
+///     type Item = ();
+///     type Error = ();
+///
+///     fn poll(&mut self) -> futures::Poll<Self::Item, Self::Error> {
+///         Self::DRIVER.poll_future(self, Self::POLL_FNS)
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Driver {
+    check_wakeups: bool,
+    livelock_limit: Option<usize>,
+    on_stall: fn(&Stall),
+    on_backpressure: Option<fn(&Backpressure)>,
+}
+
+impl Driver {
+    /// Creates a driver with the default behavior.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            check_wakeups: false,
+            livelock_limit: None,
+            on_stall: crate::diagnostics::panic_on_stall,
+            on_backpressure: None,
+        }
+    }
+
+    /// Checks that whenever the outer future or stream returns `NotReady`,
+    /// at least one of its inner futures or streams has registered to wake
+    /// up the current task during that poll.
+    ///
+    /// Returning `Ok(Async::NotReady)` from a component without having
+    /// received `NotReady` from an inner future or stream means that nothing
+    /// will ever wake up the task again, so it will hang forever. With this
+    /// check enabled, the components are polled under an instrumented task
+    /// which counts calls to `futures::task::current()`, and the driver
+    /// panics if the outer future or stream would return `NotReady` without
+    /// any having happened.
+    ///
+    /// This is only a heuristic (an inner future could have saved a task
+    /// handle during an earlier poll and not requested a new one), and it
+    /// adds overhead to every poll, so it is intended for use in tests and
+    /// debug builds.
+    #[must_use]
+    pub const fn check_wakeups(mut self, check: bool) -> Self {
+        self.check_wakeups = check;
+        self
+    }
+
+    /// Reports a [`Stall::Livelock`] if the same component returns
+    /// `Async::DidWork` for more than `limit` consecutive passes within a
+    /// single poll without any component returning a value.
+    ///
+    /// A component which returns `DidWork` without actually changing any
+    /// state would otherwise cause the driver to loop forever. Once the
+    /// stall has been reported, the driver notifies the current task and
+    /// returns `NotReady`, so that other tasks have a chance to run before
+    /// the outer future or stream is polled again.
+    ///
+    /// [`Stall::Livelock`]: diagnostics/enum.Stall.html#variant.Livelock
+    #[must_use]
+    pub const fn livelock_limit(mut self, limit: usize) -> Self {
+        self.livelock_limit = Some(limit);
+        self
+    }
+
+    /// Sets the function which is called to report stalls detected by the
+    /// driver. The default handler is
+    /// [`diagnostics::panic_on_stall`](diagnostics/fn.panic_on_stall.html).
+    #[must_use]
+    pub const fn on_stall(mut self, on_stall: fn(&Stall)) -> Self {
+        self.on_stall = on_stall;
+        self
+    }
+
+    /// Calls `on_backpressure` whenever the outer future or stream returns
+    /// `NotReady` while any of its components returned `Async::Blocked`
+    /// during the final pass.
+    ///
+    /// `Blocked` is treated the same as `NotReady` by the main loop, so this
+    /// is the only way to tell that a future or stream is stalled on its
+    /// outputs rather than waiting for its inputs, for instance to record
+    /// metrics about which stages of a pipeline are bottlenecks. By default,
+    /// no report is made.
+    #[must_use]
+    pub const fn on_backpressure(
+        mut self,
+        on_backpressure: fn(&Backpressure),
+    ) -> Self {
+        self.on_backpressure = Some(on_backpressure);
+        self
+    }
+
+    /// The body of a `futures::future::Future::poll` method.
+    ///
+    /// See [`poll_future`](fn.poll_future.html) for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if all component poll methods return `Ok(Async::NothingToDo)`,
+    /// or if any of the enabled checks fail.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error returned by any of the component poll
+    /// methods.
+    pub fn poll_future<T, C, Item, Error>(
+        &self,
+        future: &mut T,
+        poll_fns: &[C],
+    ) -> futures::Poll<Item, Error>
+    where
+        T: futures::future::Future<Item = Item, Error = Error>,
+        C: Component<T, Item, Error>,
+    {
+        self.poll(poll_fns.len(), |i| poll_fns[i].poll(future))
+    }
+
+    /// The body of a `futures::stream::Stream::poll` method.
+    ///
+    /// See [`poll_stream`](fn.poll_stream.html) for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if all component poll methods return `Ok(Async::NothingToDo)`,
+    /// or if any of the enabled checks fail.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error returned by any of the component poll
+    /// methods.
+    pub fn poll_stream<T, C, Item, Error>(
+        &self,
+        stream: &mut T,
+        poll_fns: &[C],
+    ) -> futures::Poll<Option<Item>, Error>
+    where
+        T: futures::stream::Stream<Item = Item, Error = Error>,
+        C: Component<T, Option<Item>, Error>,
+    {
+        self.poll(poll_fns.len(), |i| poll_fns[i].poll(stream))
+    }
+
+    /// The body of a `futures::future::Future::poll` method, for components
+    /// which also take a shared context argument.
+    ///
+    /// See [`poll_future_with`](fn.poll_future_with.html) for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if all component poll methods return `Ok(Async::NothingToDo)`,
+    /// or if any of the enabled checks fail.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error returned by any of the component poll
+    /// methods.
+    pub fn poll_future_with<T, Ctx, C, Item, Error>(
+        &self,
+        future: &mut T,
+        ctx: &mut Ctx,
+        poll_fns: &[C],
+    ) -> futures::Poll<Item, Error>
+    where
+        C: ComponentWith<T, Ctx, Item, Error>,
+    {
+        self.poll(poll_fns.len(), |i| poll_fns[i].poll(future, ctx))
+    }
+
+    /// The body of a `futures::stream::Stream::poll` method, for components
+    /// which also take a shared context argument.
+    ///
+    /// See [`poll_stream_with`](fn.poll_stream_with.html) for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if all component poll methods return `Ok(Async::NothingToDo)`,
+    /// or if any of the enabled checks fail.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error returned by any of the component poll
+    /// methods.
+    pub fn poll_stream_with<T, Ctx, C, Item, Error>(
+        &self,
+        stream: &mut T,
+        ctx: &mut Ctx,
+        poll_fns: &[C],
+    ) -> futures::Poll<Option<Item>, Error>
+    where
+        C: ComponentWith<T, Ctx, Option<Item>, Error>,
+    {
+        self.poll(poll_fns.len(), |i| poll_fns[i].poll(stream, ctx))
+    }
+
+    /// The body of a `futures::stream::Stream::poll` method, for streams
+    /// which return batches of items.
+    ///
+    /// See [`poll_stream_batch`](fn.poll_stream_batch.html) for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max` is zero, if all component poll methods return
+    /// `Ok(Async::NothingToDo)`, or if any of the enabled checks fail.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error returned by any of the component poll
+    /// methods.
+    pub fn poll_stream_batch<T, C, Item, Error>(
+        &self,
+        stream: &mut T,
+        max: usize,
+        poll_fns: &[C],
+    ) -> futures::Poll<Option<Vec<Item>>, Error>
+    where
+        C: Component<T, Option<Item>, Error>,
+    {
+        let mut batch = vec![];
+        Ok(self
+            .poll_stream_batch_into(stream, &mut batch, max, poll_fns)?
+            .map(|count| count.map(|_| batch)))
+    }
+
+    /// Polls a stream's components for up to `max` items, adding them to
+    /// `buf`.
+    ///
+    /// See [`poll_stream_batch_into`](fn.poll_stream_batch_into.html) for
+    /// details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max` is zero, if all component poll methods return
+    /// `Ok(Async::NothingToDo)`, or if any of the enabled checks fail.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error returned by any of the component poll
+    /// methods.
+    pub fn poll_stream_batch_into<T, B, C, Item, Error>(
+        &self,
+        stream: &mut T,
+        buf: &mut B,
+        max: usize,
+        poll_fns: &[C],
+    ) -> futures::Poll<Option<usize>, Error>
+    where
+        B: Extend<Item>,
+        C: Component<T, Option<Item>, Error>,
+    {
+        assert!(max > 0, "batch size must be greater than zero");
+
+        let mut count = 0;
+        while count < max {
+            match self.poll(poll_fns.len(), |i| poll_fns[i].poll(stream))? {
+                futures::Async::Ready(Some(item)) => {
+                    buf.extend(std::iter::once(item));
+                    count += 1;
+                }
+                futures::Async::Ready(None) if count == 0 => {
+                    return Ok(futures::Async::Ready(None));
+                }
+                futures::Async::NotReady if count == 0 => {
+                    return Ok(futures::Async::NotReady);
+                }
+                futures::Async::Ready(None) | futures::Async::NotReady => {
+                    break;
+                }
+            }
+        }
+        Ok(futures::Async::Ready(Some(count)))
+    }
+
+    /// The body of a `futures::stream::Stream::poll` method, for components
+    /// which can emit several items at once.
+    ///
+    /// See [`poll_stream_queued`](fn.poll_stream_queued.html) for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if all component poll methods return `Ok(Async::NothingToDo)`,
+    /// or if any of the enabled checks fail.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error returned by any of the component poll
+    /// methods.
+    pub fn poll_stream_queued<T, C, Item, Error>(
+        &self,
+        stream: &mut T,
+        queue: &mut Queue<Item>,
+        poll_fns: &[C],
+    ) -> futures::Poll<Option<Item>, Error>
+    where
+        C: ComponentWith<T, Queue<Item>, Option<Item>, Error>,
+    {
+        if let Some(item) = queue.pop() {
+            return Ok(futures::Async::Ready(Some(item)));
+        }
+
+        self.poll(poll_fns.len(), |i| {
+            let res = poll_fns[i].poll(stream, queue)?;
+            if queue.is_empty() {
+                return Ok(res);
+            }
+            if let Async::Ready(Some(item)) = res {
+                queue.push(item);
+            }
+            Ok(Async::Ready(queue.pop()))
+        })
+    }
+
+    /// The body of a `futures::future::Future::poll` method, for futures
+    /// which should fail if they take too long.
+    ///
+    /// See [`poll_future_until`](fn.poll_future_until.html) for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if all component poll methods return `Ok(Async::NothingToDo)`,
+    /// or if any of the enabled checks fail.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error returned by any of the component poll
+    /// methods, or the error created by the deadline if it expires.
+    pub fn poll_future_until<T, Tm, C, Item, Error>(
+        &self,
+        future: &mut T,
+        deadline: fn(&mut T) -> &mut Deadline<Tm, Error>,
+        poll_fns: &[C],
+    ) -> futures::Poll<Item, Error>
+    where
+        T: futures::future::Future<Item = Item, Error = Error>,
+        Tm: Timer,
+        C: Component<T, Item, Error>,
+    {
+        self.poll_until(future, deadline, poll_fns)
+    }
+
+    /// The body of a `futures::stream::Stream::poll` method, for streams
+    /// which should fail if they take too long.
+    ///
+    /// See [`poll_stream_until`](fn.poll_stream_until.html) for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if all component poll methods return `Ok(Async::NothingToDo)`,
+    /// or if any of the enabled checks fail.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error returned by any of the component poll
+    /// methods, or the error created by the deadline if it expires.
+    pub fn poll_stream_until<T, Tm, C, Item, Error>(
+        &self,
+        stream: &mut T,
+        deadline: fn(&mut T) -> &mut Deadline<Tm, Error>,
+        poll_fns: &[C],
+    ) -> futures::Poll<Option<Item>, Error>
+    where
+        T: futures::stream::Stream<Item = Item, Error = Error>,
+        Tm: Timer,
+        C: Component<T, Option<Item>, Error>,
+    {
+        self.poll_until(stream, deadline, poll_fns)
+    }
+
+    /// The body of a `futures::future::Future::poll` method, for futures
+    /// whose components are only polled while their guards allow it.
+    ///
+    /// See [`poll_future_guarded`](fn.poll_future_guarded.html) for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if all component poll methods return `Ok(Async::NothingToDo)`
+    /// or are disabled by their guards, or if any of the enabled checks
+    /// fail.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error returned by any of the component poll
+    /// methods.
+    pub fn poll_future_guarded<T, C, Item, Error>(
+        &self,
+        future: &mut T,
+        poll_fns: &[(fn(&T) -> bool, C)],
+    ) -> futures::Poll<Item, Error>
+    where
+        T: futures::future::Future<Item = Item, Error = Error>,
+        C: Component<T, Item, Error>,
+    {
+        self.poll_guarded(future, poll_fns)
+    }
+
+    /// The body of a `futures::stream::Stream::poll` method, for streams
+    /// whose components are only polled while their guards allow it.
+    ///
+    /// See [`poll_stream_guarded`](fn.poll_stream_guarded.html) for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if all component poll methods return `Ok(Async::NothingToDo)`
+    /// or are disabled by their guards, or if any of the enabled checks
+    /// fail.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error returned by any of the component poll
+    /// methods.
+    pub fn poll_stream_guarded<T, C, Item, Error>(
+        &self,
+        stream: &mut T,
+        poll_fns: &[(fn(&T) -> bool, C)],
+    ) -> futures::Poll<Option<Item>, Error>
+    where
+        T: futures::stream::Stream<Item = Item, Error = Error>,
+        C: Component<T, Option<Item>, Error>,
+    {
+        self.poll_guarded(stream, poll_fns)
+    }
+
+    fn poll_guarded<T, C, Item, Error>(
+        self,
+        target: &mut T,
+        poll_fns: &[(fn(&T) -> bool, C)],
+    ) -> futures::Poll<Item, Error>
+    where
+        C: Component<T, Item, Error>,
+    {
+        let mut idle = true;
+        self.poll(poll_fns.len(), |i| {
+            let (guard, component) = &poll_fns[i];
+            let res = if guard(target) {
+                component.poll(target)
+            } else {
+                Ok(Async::NothingToDo)
+            };
+
+            // report the pass which would otherwise hit the generic panic in
+            // run. nothing did any work during this pass, so the guards
+            // still give the same answers as when they were checked.
+            idle = (i == 0 || idle) && matches!(res, Ok(Async::NothingToDo));
+            if idle && i + 1 == poll_fns.len() {
+                let target = &*target;
+                panic!(
+                    "{}",
+                    Idle {
+                        disabled: poll_fns
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, (guard, _))| !guard(target))
+                            .map(|(i, _)| i)
+                            .collect(),
+                    }
+                );
+            }
+            res
+        })
+    }
+
+    /// The body of a `futures::future::Future::poll` method, for futures
+    /// whose components can be enabled and disabled at runtime.
+    ///
+    /// See [`poll_future_masked`](fn.poll_future_masked.html) for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mask doesn't have one entry per component, if all
+    /// component poll methods return `Ok(Async::NothingToDo)` or are
+    /// disabled, or if any of the enabled checks fail.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error returned by any of the component poll
+    /// methods.
+    pub fn poll_future_masked<T, C, Item, Error>(
+        &self,
+        future: &mut T,
+        mask: fn(&mut T) -> &mut Mask,
+        poll_fns: &[C],
+    ) -> futures::Poll<Item, Error>
+    where
+        T: futures::future::Future<Item = Item, Error = Error>,
+        C: Component<T, Item, Error>,
+    {
+        self.poll_masked(future, mask, poll_fns)
+    }
+
+    /// The body of a `futures::stream::Stream::poll` method, for streams
+    /// whose components can be enabled and disabled at runtime.
+    ///
+    /// See [`poll_stream_masked`](fn.poll_stream_masked.html) for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mask doesn't have one entry per component, if all
+    /// component poll methods return `Ok(Async::NothingToDo)` or are
+    /// disabled, or if any of the enabled checks fail.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error returned by any of the component poll
+    /// methods.
+    pub fn poll_stream_masked<T, C, Item, Error>(
+        &self,
+        stream: &mut T,
+        mask: fn(&mut T) -> &mut Mask,
+        poll_fns: &[C],
+    ) -> futures::Poll<Option<Item>, Error>
+    where
+        T: futures::stream::Stream<Item = Item, Error = Error>,
+        C: Component<T, Option<Item>, Error>,
+    {
+        self.poll_masked(stream, mask, poll_fns)
+    }
+
+    fn poll_masked<T, C, Item, Error>(
+        self,
+        target: &mut T,
+        mask: fn(&mut T) -> &mut Mask,
+        poll_fns: &[C],
+    ) -> futures::Poll<Item, Error>
+    where
+        C: Component<T, Item, Error>,
+    {
+        assert_eq!(
+            mask(target).len(),
+            poll_fns.len(),
+            "the mask must have one entry per component"
+        );
+
+        // changes are applied at the start of each pass, and an extra
+        // component at the end of each pass forces another one if the mask
+        // was changed during it
+        self.poll(poll_fns.len() + 1, |i| {
+            let mask = mask(target);
+            if i == 0 {
+                mask.apply();
+            }
+            if i == poll_fns.len() {
+                Ok(if mask.is_changed() {
+                    Async::DidWork
+                } else {
+                    Async::NothingToDo
+                })
+            } else if mask.is_active(i) {
+                poll_fns[i].poll(target)
+            } else {
+                Ok(Async::NothingToDo)
+            }
+        })
+    }
+
+    /// The body of a `futures::future::Future::poll` method, for futures
+    /// whose components run in sequential phases.
+    ///
+    /// See [`poll_future_phased`](fn.poll_future_phased.html) for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the current phase is out of range, if all component poll
+    /// methods of the last phase return `Ok(Async::NothingToDo)`, or if any
+    /// of the enabled checks fail.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error returned by any of the component poll
+    /// methods.
+    pub fn poll_future_phased<T, C, Item, Error>(
+        &self,
+        future: &mut T,
+        phases: fn(&mut T) -> &mut Phases,
+        poll_fns: &[&[C]],
+    ) -> futures::Poll<Item, Error>
+    where
+        T: futures::future::Future<Item = Item, Error = Error>,
+        C: Component<T, Item, Error>,
+    {
+        self.poll_phased(future, phases, poll_fns)
+    }
+
+    /// The body of a `futures::stream::Stream::poll` method, for streams
+    /// whose components run in sequential phases.
+    ///
+    /// See [`poll_stream_phased`](fn.poll_stream_phased.html) for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the current phase is out of range, if all component poll
+    /// methods of the last phase return `Ok(Async::NothingToDo)`, or if any
+    /// of the enabled checks fail.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error returned by any of the component poll
+    /// methods.
+    pub fn poll_stream_phased<T, C, Item, Error>(
+        &self,
+        stream: &mut T,
+        phases: fn(&mut T) -> &mut Phases,
+        poll_fns: &[&[C]],
+    ) -> futures::Poll<Option<Item>, Error>
+    where
+        T: futures::stream::Stream<Item = Item, Error = Error>,
+        C: Component<T, Option<Item>, Error>,
+    {
+        self.poll_phased(stream, phases, poll_fns)
+    }
+
+    fn poll_phased<T, C, Item, Error>(
+        self,
+        target: &mut T,
+        phases: fn(&mut T) -> &mut Phases,
+        poll_fns: &[&[C]],
+    ) -> futures::Poll<Item, Error>
+    where
+        C: Component<T, Item, Error>,
+    {
+        assert!(
+            phases(target).current() < poll_fns.len(),
+            "the current phase is out of range"
+        );
+        let len = poll_fns.iter().map(|phase| phase.len()).max().unwrap_or(0);
+        let mut busy = false;
+
+        // every pass covers as many components as the largest phase, plus
+        // an extra component at the end which moves on to the next phase if
+        // none of the components of the current phase did anything
+        self.poll(len + 1, |i| {
+            let current = phases(target).current();
+            if i < len {
+                let res = poll_fns[current]
+                    .get(i)
+                    .map_or(Ok(Async::NothingToDo), |c| c.poll(target));
+                busy |= !matches!(res, Ok(Async::NothingToDo));
+                res
+            } else if !std::mem::take(&mut busy)
+                && current + 1 < poll_fns.len()
+            {
+                phases(target).advance();
+                Ok(Async::DidWork)
+            } else {
+                Ok(Async::NothingToDo)
+            }
+        })
+    }
+
+    fn poll_until<T, Tm, C, Item, Error>(
+        self,
+        target: &mut T,
+        deadline: fn(&mut T) -> &mut Deadline<Tm, Error>,
+        poll_fns: &[C],
+    ) -> futures::Poll<Item, Error>
+    where
+        Tm: Timer,
+        C: Component<T, Item, Error>,
+    {
+        // the pending components are only needed for the report once the
+        // deadline has expired, so don't allocate for them otherwise
+        let expired = deadline(target).is_expired();
+        let mut pending = vec![];
+        let res = self.poll(poll_fns.len(), |i| {
+            let res = poll_fns[i].poll(target);
+            if expired {
+                if i == 0 {
+                    pending.clear();
+                }
+                if matches!(res, Ok(Async::NotReady | Async::Blocked)) {
+                    pending.push(i);
+                }
+            }
+            res
+        });
+        deadline(target).check(res, expired.then_some(pending))
+    }
+
+    /// The body of a `futures::stream::Stream::poll` method, for streams
+    /// which can be shut down gracefully.
+    ///
+    /// See [`poll_stream_shutdown`](fn.poll_stream_shutdown.html) for
+    /// details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if all component poll methods return `Ok(Async::NothingToDo)`
+    /// before shutdown has been triggered, or if any of the enabled checks
+    /// fail.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error returned by any of the component poll
+    /// methods.
+    pub fn poll_stream_shutdown<T, C, Item, Error>(
+        &self,
+        stream: &mut T,
+        signal: fn(&T) -> &Signal,
+        poll_fns: &[(Role, C)],
+    ) -> futures::Poll<Option<Item>, Error>
+    where
+        T: futures::stream::Stream<Item = Item, Error = Error>,
+        C: Component<T, Option<Item>, Error>,
+    {
+        let shutdown = signal(stream).poll_triggered();
+        let mut busy = false;
+
+        // an extra component at the end of each pass ends the stream if
+        // shutdown has been triggered and no drain component did anything
+        self.poll(poll_fns.len() + 1, |i| {
+            if let Some((role, component)) = poll_fns.get(i) {
+                if shutdown && *role == Role::Intake {
+                    return Ok(Async::NothingToDo);
+                }
+                let res = component.poll(stream);
+                busy |= !matches!(res, Ok(Async::NothingToDo));
+                res
+            } else if shutdown && !std::mem::take(&mut busy) {
+                Ok(Async::Ready(None))
+            } else {
+                busy = false;
+                Ok(Async::NothingToDo)
+            }
+        })
+    }
+
+    fn poll<Item, Error>(
+        self,
+        len: usize,
+        mut poll_one: impl FnMut(usize) -> Poll<Item, Error>,
+    ) -> futures::Poll<Item, Error> {
+        if !self.check_wakeups {
+            return self.run(len, &mut poll_one);
+        }
+
+        let registrations = Arc::new(Registrations {
+            task: futures::task::current(),
+            count: AtomicUsize::new(0),
+        });
+        let res = futures::executor::with_notify(&registrations, 0, || {
+            self.run(len, &mut poll_one)
+        });
+        if matches!(res, Ok(futures::Async::NotReady)) {
+            assert!(
+                registrations.count.load(Ordering::SeqCst) > 0,
+                "returned NotReady without registering for a wakeup"
+            );
+        }
+        res
+    }
+
+    fn run<Item, Error>(
+        self,
+        len: usize,
+        poll_one: &mut impl FnMut(usize) -> Poll<Item, Error>,
+    ) -> futures::Poll<Item, Error> {
+        let mut did_work_counts = if self.livelock_limit.is_some() {
+            vec![0; len]
+        } else {
+            vec![]
+        };
+
+        let mut blocked = vec![];
+
+        loop {
+            let mut not_ready = false;
+            let mut did_work = false;
+            blocked.clear();
+
+            for i in 0..len {
+                let res = poll_one(i)?;
+                if let (Some(limit), Some(count)) =
+                    (self.livelock_limit, did_work_counts.get_mut(i))
+                {
+                    if matches!(res, Async::DidWork) {
+                        *count += 1;
+                    } else {
+                        *count = 0;
+                    }
+                    if *count > limit {
+                        (self.on_stall)(&Stall::Livelock {
+                            component: i,
+                            iterations: *count,
+                        });
+                        futures::task::current().notify();
+                        return Ok(futures::Async::NotReady);
+                    }
+                }
+
+                match res {
+                    Async::Ready(e) => return Ok(futures::Async::Ready(e)),
+                    Async::NotReady => not_ready = true,
+                    Async::Blocked => {
+                        not_ready = true;
+                        if self.on_backpressure.is_some() {
+                            blocked.push(i);
+                        }
+                    }
+                    Async::NothingToDo => {}
+                    Async::DidWork => did_work = true,
+                    Async::Yield => {
+                        futures::task::current().notify();
+                        return Ok(futures::Async::NotReady);
+                    }
+                }
+            }
+
+            if !did_work {
+                if not_ready {
+                    if let Some(on_backpressure) = self.on_backpressure {
+                        if !blocked.is_empty() {
+                            on_backpressure(&Backpressure { blocked });
+                        }
+                    }
+                    return Ok(futures::Async::NotReady);
+                }
+                unreachable!()
+            }
+        }
+    }
+}
+
+impl Default for Driver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// forwards notifications to the real task, while counting the number of
+// times an inner future or stream asked for a handle to it
+struct Registrations {
+    task: futures::task::Task,
+    count: AtomicUsize,
+}
+
+impl futures::executor::Notify for Registrations {
+    fn notify(&self, _id: usize) {
+        self.task.notify();
+    }
+
+    fn clone_id(&self, id: usize) -> usize {
+        self.count.fetch_add(1, Ordering::SeqCst);
+        id
+    }
+}
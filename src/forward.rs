@@ -0,0 +1,175 @@
+//! A component which moves items from an inner stream into an inner sink.
+//!
+//! Forwarding from a stream to a sink by hand requires holding on to the
+//! item returned in `AsyncSink::NotReady`, flushing the sink with
+//! `poll_complete` whenever the stream has nothing more to give, and
+//! closing the sink once the stream ends. A [`Forward`] stored on the outer
+//! future or stream takes care of all of this, and its
+//! [`poll`](Forward::poll) method can be used as a component with
+//! [`Project`](../project/struct.Project.html).
+//!
+//! # Examples
+//!
+//! ```
+//! use component_future::forward::Forward;
+//! use component_future::project::Project;
+//!
+//! type Input = futures::sync::mpsc::UnboundedReceiver<u32>;
+//! type Output = futures::sync::mpsc::Sender<u32>;
+//!
+//! #[derive(Debug)]
+//! enum Error {
+//!     Recv,
+//!     Send(futures::sync::mpsc::SendError<u32>),
+//! }
+//! # impl From<()> for Error {
+//! #     fn from((): ()) -> Self {
+//! #         Self::Recv
+//! #     }
+//! # }
+//! # impl From<futures::sync::mpsc::SendError<u32>> for Error {
+//! #     fn from(e: futures::sync::mpsc::SendError<u32>) -> Self {
+//! #         Self::Send(e)
+//! #     }
+//! # }
+//!
+//! struct Relay {
+//!     forward: Forward<Input, Output>,
+//!     // ...
+//! }
+//!
+//! impl Relay {
+//!     const POLL_FNS: &'static [&'static dyn component_future::Component<
+//!         Self,
+//!         (),
+//!         Error,
+//!     >] = &[
+//!         &Project::new(
+//!             |relay: &mut Self| &mut relay.forward,
+//!             &Forward::poll,
+//!         ),
+//!         &Self::poll_done,
+//!     ];
+//!
+//!     fn poll_done(&mut self) -> component_future::Poll<(), Error> {
+//!         if self.forward.is_done() {
+//!             Ok(component_future::Async::Ready(()))
+//!         } else {
+//!             Ok(component_future::Async::NothingToDo)
+//!         }
+//!     }
+//! }
+//!
+//! impl futures::future::Future for Relay {
+//!     type Item = ();
+//!     type Error = Error;
+//!
+//!     fn poll(&mut self) -> futures::Poll<Self::Item, Self::Error> {
+//!         component_future::poll_future(self, Self::POLL_FNS)
+//!     }
+//! }
+//! ```
+
+/// The state of a stream being forwarded into a sink.
+#[derive(Debug)]
+pub struct Forward<St, Si>
+where
+    St: futures::stream::Stream,
+{
+    stream: St,
+    sink: Si,
+    buffered: Option<St::Item>,
+    state: State,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Forwarding,
+    Closing,
+    Done,
+}
+
+impl<St, Si> Forward<St, Si>
+where
+    St: futures::stream::Stream,
+    Si: futures::sink::Sink<SinkItem = St::Item>,
+{
+    /// Creates a new forwarding state, which sends every item from `stream`
+    /// into `sink`, and closes `sink` when `stream` ends.
+    pub const fn new(stream: St, sink: Si) -> Self {
+        Self {
+            stream,
+            sink,
+            buffered: None,
+            state: State::Forwarding,
+        }
+    }
+
+    /// Returns true once the stream has ended and the sink has been closed.
+    pub fn is_done(&self) -> bool {
+        self.state == State::Done
+    }
+
+    /// Returns the stream and the sink, along with the item which was
+    /// received from the stream but not yet accepted by the sink, if any.
+    pub fn into_inner(self) -> (St, Si, Option<St::Item>) {
+        (self.stream, self.sink, self.buffered)
+    }
+
+    /// Moves a single item from the stream into the sink, or makes progress
+    /// on flushing or closing the sink.
+    ///
+    /// This returns `Async::DidWork` whenever an item was received or sent,
+    /// or the sink was closed, `Async::Blocked` if the sink is unable to
+    /// accept more items or finish flushing or closing, `Async::NotReady`
+    /// if the stream has nothing more to give and the sink has been
+    /// flushed, and `Async::NothingToDo` once everything is done. It never
+    /// returns `Async::Ready`.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error produced by the stream or the sink.
+    pub fn poll<Item, Error>(&mut self) -> crate::Poll<Item, Error>
+    where
+        Error: From<St::Error> + From<Si::SinkError>,
+    {
+        if let Some(item) = self.buffered.take() {
+            if let futures::AsyncSink::NotReady(item) =
+                self.sink.start_send(item)?
+            {
+                self.buffered = Some(item);
+                return Ok(crate::Async::Blocked);
+            }
+            return Ok(crate::Async::DidWork);
+        }
+
+        match self.state {
+            State::Forwarding => match self.stream.poll()? {
+                futures::Async::Ready(Some(item)) => {
+                    self.buffered = Some(item);
+                    Ok(crate::Async::DidWork)
+                }
+                futures::Async::Ready(None) => {
+                    self.state = State::Closing;
+                    Ok(crate::Async::DidWork)
+                }
+                futures::Async::NotReady => {
+                    if self.sink.poll_complete()?.is_ready() {
+                        Ok(crate::Async::NotReady)
+                    } else {
+                        Ok(crate::Async::Blocked)
+                    }
+                }
+            },
+            State::Closing => {
+                if self.sink.close()?.is_ready() {
+                    self.state = State::Done;
+                    Ok(crate::Async::DidWork)
+                } else {
+                    Ok(crate::Async::Blocked)
+                }
+            }
+            State::Done => Ok(crate::Async::NothingToDo),
+        }
+    }
+}
@@ -88,7 +88,18 @@
 //!     }
 //! }
 //! ```
+//!
+//! # Features
+//!
+//! * `std` (enabled by default): provides the drivers for `futures` 0.1
+//!   futures and streams. Without this feature, the crate is `no_std`, and
+//!   only the `core::task` based driver in the [`task`](task/index.html)
+//!   module is available.
+//! * `compat`: provides the [`compat`](compat/index.html) module, for using
+//!   `futures` 0.1 components with the `core::task` based driver and vice
+//!   versa.
 
+#![cfg_attr(not(feature = "std"), no_std)]
 // XXX this is broken with ale
 // #![warn(clippy::cargo)]
 #![warn(clippy::pedantic)]
@@ -98,6 +109,38 @@
 
 const _DUMMY_DEPENDENCY: &str = include_str!("../Cargo.toml");
 
+#[cfg(feature = "std")]
+pub mod cancel;
+#[cfg(feature = "compat")]
+pub mod compat;
+#[cfg(feature = "std")]
+pub mod diagnostics;
+#[cfg(feature = "std")]
+mod driver;
+#[cfg(feature = "std")]
+pub mod forward;
+pub mod map;
+#[cfg(feature = "std")]
+pub mod mask;
+#[cfg(feature = "std")]
+pub mod merge;
+#[cfg(feature = "std")]
+pub mod phases;
+pub mod project;
+#[cfg(feature = "std")]
+pub mod queue;
+#[cfg(feature = "std")]
+pub mod shutdown;
+pub mod supervise;
+pub mod task;
+#[cfg(feature = "std")]
+pub mod testing;
+#[cfg(feature = "std")]
+pub mod timeout;
+
+#[cfg(feature = "std")]
+pub use driver::Driver;
+
 /// Return type of a component of a future or stream, indicating whether a
 /// value is ready, or if not, what actions were taken.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -124,6 +167,38 @@ pub enum Async<Item> {
     /// be polled again. It is an error (panic) for all component poll methods
     /// to return `NothingToDo`.
     NothingToDo,
+
+    /// We still have more work to do, but want to give other tasks a chance
+    /// to run first (for instance, after processing a large number of
+    /// buffered messages). The main loop will notify the current task (so
+    /// that it will be polled again soon) and immediately return
+    /// `NotReady`, without running any of the remaining components.
+    Yield,
+
+    /// One of our outbound sinks (or some other downstream resource) is
+    /// full, so we can't make progress until it has room again. The main
+    /// loop treats this the same as `NotReady`, but it is reported
+    /// separately so that backpressure can be distinguished from waiting
+    /// for input (see
+    /// [`Driver::on_backpressure`](struct.Driver.html#method.on_backpressure)).
+    Blocked,
+}
+
+impl<Item> Async<Item> {
+    /// Changes the ready value of this `Async` with the closure provided.
+    pub fn map<U, F>(self, f: F) -> Async<U>
+    where
+        F: FnOnce(Item) -> U,
+    {
+        match self {
+            Self::Ready(t) => Async::Ready(f(t)),
+            Self::NotReady => Async::NotReady,
+            Self::DidWork => Async::DidWork,
+            Self::NothingToDo => Async::NothingToDo,
+            Self::Yield => Async::Yield,
+            Self::Blocked => Async::Blocked,
+        }
+    }
 }
 
 /// Each component poll method should return a value of this type.
@@ -136,15 +211,90 @@ pub enum Async<Item> {
 /// * `Ok(Async::DidWork)` means that the overall future made progress by
 ///   updating its internal state, but isn't yet ready to return a value.
 /// * `Ok(Async::NothingToDo)` means that no work was done at all.
+/// * `Ok(Async::Yield)` means that there is more work to do, but the overall
+///   future or stream should return `NotReady` to let other tasks run first.
+/// * `Ok(Async::Blocked)` means the same thing as `Ok(Async::NotReady)`,
+///   except that the component is waiting for a downstream sink to have
+///   room rather than for new input.
 /// * `Err(e)` means that the overall future or stream is ready to return an
 ///   error.
 pub type Poll<Item, Error> = Result<Async<Item>, Error>;
 
+/// A single component of a future or stream.
+///
+/// This is implemented for any function of the form
+/// `Fn(&mut T) -> component_future::Poll<Item, Error>`, so a slice of
+/// component poll methods can be passed to `poll_future` or `poll_stream`
+/// directly. Wrappers such as
+/// [`supervise::Supervised`](supervise/struct.Supervised.html) also
+/// implement it, and can be mixed with plain poll methods by using a slice
+/// of `&dyn Component<T, Item, Error>`.
+pub trait Component<T, Item, Error> {
+    /// Runs this component against the outer future or stream.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the outer future or stream should return that
+    /// error.
+    fn poll(&self, target: &mut T) -> Poll<Item, Error>;
+}
+
+impl<T, Item, Error, F> Component<T, Item, Error> for F
+where
+    F: Fn(&mut T) -> Poll<Item, Error>,
+{
+    fn poll(&self, target: &mut T) -> Poll<Item, Error> {
+        self(target)
+    }
+}
+
+impl<'a, T, Item, Error> Component<T, Item, Error>
+    for &'a (dyn Component<T, Item, Error> + 'a)
+{
+    fn poll(&self, target: &mut T) -> Poll<Item, Error> {
+        (**self).poll(target)
+    }
+}
+
+/// A single component of a future or stream which also takes a shared
+/// context argument.
+///
+/// This is implemented for any function of the form
+/// `Fn(&mut T, &mut Ctx) -> component_future::Poll<Item, Error>`, and is
+/// used by `poll_future_with` and `poll_stream_with`.
+pub trait ComponentWith<T, Ctx, Item, Error> {
+    /// Runs this component against the outer future or stream.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the outer future or stream should return that
+    /// error.
+    fn poll(&self, target: &mut T, ctx: &mut Ctx) -> Poll<Item, Error>;
+}
+
+impl<T, Ctx, Item, Error, F> ComponentWith<T, Ctx, Item, Error> for F
+where
+    F: Fn(&mut T, &mut Ctx) -> Poll<Item, Error>,
+{
+    fn poll(&self, target: &mut T, ctx: &mut Ctx) -> Poll<Item, Error> {
+        self(target, ctx)
+    }
+}
+
+impl<'a, T, Ctx, Item, Error> ComponentWith<T, Ctx, Item, Error>
+    for &'a (dyn ComponentWith<T, Ctx, Item, Error> + 'a)
+{
+    fn poll(&self, target: &mut T, ctx: &mut Ctx) -> Poll<Item, Error> {
+        (**self).poll(target, ctx)
+    }
+}
+
 /// A macro for extracting the successful type of a `futures::Poll<T, E>` and
 /// turning it into a `component_future::Poll<T, E>`.
 ///
 /// This macro propagates both errors and `NotReady` values by returning
 /// early.
+#[cfg(feature = "std")]
 #[macro_export]
 macro_rules! try_ready {
     ($e:expr) => {
@@ -158,6 +308,130 @@ macro_rules! try_ready {
     };
 }
 
+/// A macro for extracting the successful type of a
+/// `core::task::Poll<Result<T, E>>` and turning it into a
+/// `component_future::Poll<T, E>`.
+///
+/// This macro propagates both errors and `Pending` values by returning
+/// early. To use it with the result of polling a stream whose items are
+/// `Result`s, call `.map(Option::transpose)` on the result first.
+#[macro_export]
+macro_rules! try_ready_task {
+    ($e:expr) => {
+        match $e {
+            ::core::task::Poll::Ready(Ok(t)) => t,
+            ::core::task::Poll::Ready(Err(e)) => {
+                return Err(::core::convert::From::from(e))
+            }
+            ::core::task::Poll::Pending => return Ok($crate::Async::NotReady),
+        }
+    };
+}
+
+/// A macro for declaring a state machine whose states may each hold an
+/// inner future.
+///
+/// This generates an enum with one variant per state, along with an
+/// associated `poll` function which can be used as a component of the outer
+/// future or stream (which must store the state in the named field). States
+/// without an inner future are idle, and the component returns
+/// `Async::NothingToDo` while in them. States with an inner future poll it,
+/// and once it resolves, call the given transition (a function, or a
+/// closure which doesn't capture anything) with the outer future or stream
+/// and the resolved value. The transition returns the next state along with
+/// the `Async` value that the component should return. Errors
+/// from the inner futures are converted with `From::from`, as with
+/// [`try_ready!`].
+///
+/// # Examples
+///
+/// ```
+/// # #[derive(Debug, PartialEq, Eq)]
+/// # struct OutputEvent(u32);
+/// # type Output =
+/// #     Box<dyn futures::future::Future<Item = OutputEvent, Error = String>>;
+/// component_future::state_machine! {
+///     enum State for Server.state -> Poll<Option<OutputEvent>, String> {
+///         Reading,
+///         Processing(Output) => |_server, event| {
+///             (State::Reading, component_future::Async::Ready(Some(event)))
+///         },
+///     }
+/// }
+///
+/// struct Server {
+///     state: State,
+///     // ...
+/// }
+///
+/// impl Server {
+///     const POLL_FNS: &'static [&'static dyn component_future::Component<
+///         Self,
+///         Option<OutputEvent>,
+///         String,
+///     >] = &[&State::poll];
+/// }
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! state_machine {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident for $outer:ident.$field:ident
+            -> Poll<$item:ty, $error:ty>
+        {
+            $($state:ident $(($fut:ty) => $transition:expr)?),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis enum $name {
+            $($state $(($fut))?),*
+        }
+
+        impl $name {
+            /// Polls the inner future of the current state, if any, and
+            /// moves to the next state once it resolves.
+            $vis fn poll(outer: &mut $outer) -> $crate::Poll<$item, $error> {
+                match &mut outer.$field {
+                    $(
+                        $name::$state $((
+                            $crate::state_machine!(@bind $fut, fut)
+                        ))? => $crate::state_machine!(
+                            @poll
+                            $name,
+                            $outer,
+                            outer.$field,
+                            $item,
+                            fut
+                            $(, $fut, $transition)?
+                        ),
+                    )*
+                }
+            }
+        }
+    };
+    (@bind $fut:ty, $binding:ident) => {
+        $binding
+    };
+    (@poll $name:ident, $outer:ident, $outer_ident:ident.$field:ident,
+        $item:ty, $binding:ident) => {
+        Ok($crate::Async::NothingToDo)
+    };
+    (@poll $name:ident, $outer:ident, $outer_ident:ident.$field:ident,
+        $item:ty, $binding:ident, $fut:ty, $transition:expr) => {{
+        let val = $crate::try_ready!(
+            futures::future::Future::poll($binding)
+        );
+        let transition: fn(
+            &mut $outer,
+            <$fut as futures::future::Future>::Item,
+        ) -> ($name, $crate::Async<$item>) = $transition;
+        let (next, res) = transition($outer_ident, val);
+        $outer_ident.$field = next;
+        Ok(res)
+    }};
+}
+
 /// The body of a `futures::future::Future::poll` method.
 ///
 /// It will repeatedly call the given component poll functions until none of
@@ -168,6 +442,10 @@ macro_rules! try_ready {
 ///
 /// Panics if all component poll methods return `Ok(Async::NothingToDo)`.
 ///
+/// # Errors
+///
+/// Returns the first error returned by any of the component poll methods.
+///
 /// # Examples
 ///
 /// ```
@@ -188,34 +466,16 @@ macro_rules! try_ready {
 ///     }
 /// }
 /// ```
-pub fn poll_future<'a, T, Item, Error>(
+#[cfg(feature = "std")]
+pub fn poll_future<T, C, Item, Error>(
     future: &mut T,
-    poll_fns: &'a [&'a dyn for<'b> Fn(&'b mut T) -> Poll<Item, Error>],
+    poll_fns: &[C],
 ) -> futures::Poll<Item, Error>
 where
     T: futures::future::Future<Item = Item, Error = Error>,
+    C: Component<T, Item, Error>,
 {
-    loop {
-        let mut not_ready = false;
-        let mut did_work = false;
-
-        for f in poll_fns {
-            match f(future)? {
-                Async::Ready(e) => return Ok(futures::Async::Ready(e)),
-                Async::NotReady => not_ready = true,
-                Async::NothingToDo => {}
-                Async::DidWork => did_work = true,
-            }
-        }
-
-        if !did_work {
-            if not_ready {
-                return Ok(futures::Async::NotReady);
-            } else {
-                unreachable!()
-            }
-        }
-    }
+    Driver::new().poll_future(future, poll_fns)
 }
 
 /// The body of a `futures::stream::Stream::poll` method.
@@ -228,6 +488,10 @@ where
 ///
 /// Panics if all component poll methods return `Ok(Async::NothingToDo)`.
 ///
+/// # Errors
+///
+/// Returns the first error returned by any of the component poll methods.
+///
 /// # Examples
 ///
 /// ```
@@ -248,34 +512,481 @@ where
 ///     }
 /// }
 /// ```
-pub fn poll_stream<'a, T, Item, Error>(
+#[cfg(feature = "std")]
+pub fn poll_stream<T, C, Item, Error>(
     stream: &mut T,
-    poll_fns: &'a [&'a dyn for<'b> Fn(
-        &'b mut T,
-    ) -> Poll<Option<Item>, Error>],
+    poll_fns: &[C],
 ) -> futures::Poll<Option<Item>, Error>
 where
     T: futures::stream::Stream<Item = Item, Error = Error>,
+    C: Component<T, Option<Item>, Error>,
 {
-    loop {
-        let mut not_ready = false;
-        let mut did_work = false;
-
-        for f in poll_fns {
-            match f(stream)? {
-                Async::Ready(e) => return Ok(futures::Async::Ready(e)),
-                Async::NotReady => not_ready = true,
-                Async::NothingToDo => {}
-                Async::DidWork => did_work = true,
-            }
-        }
+    Driver::new().poll_stream(stream, poll_fns)
+}
 
-        if !did_work {
-            if not_ready {
-                return Ok(futures::Async::NotReady);
-            } else {
-                unreachable!()
-            }
-        }
-    }
+/// The body of a `futures::stream::Stream::poll` method, for streams which
+/// return batches of items.
+///
+/// This behaves like [`poll_stream`], except that instead of returning as
+/// soon as a single component returns `Ok(Async::Ready(Some(item)))`, it
+/// keeps polling the components and collects up to `max` items into a
+/// `Vec`. The batch is returned as soon as it is full, or as soon as the
+/// components can't make any more progress (in which case the batch may be
+/// smaller than `max`, but will never be empty).
+///
+/// If the stream ends after some items have already been collected, those
+/// items are returned first, and the end of the stream is reported the next
+/// time the stream is polled, so components must keep returning
+/// `Ok(Async::Ready(None))` once the stream has ended. If a component
+/// returns an error, the error is returned immediately and any items
+/// collected during that poll are dropped; use [`poll_stream_batch_into`]
+/// to avoid this.
+///
+/// # Panics
+///
+/// Panics if `max` is zero, or if all component poll methods return
+/// `Ok(Async::NothingToDo)`.
+///
+/// # Errors
+///
+/// Returns the first error returned by any of the component poll methods.
+#[cfg(feature = "std")]
+pub fn poll_stream_batch<T, C, Item, Error>(
+    stream: &mut T,
+    max: usize,
+    poll_fns: &[C],
+) -> futures::Poll<Option<Vec<Item>>, Error>
+where
+    C: Component<T, Option<Item>, Error>,
+{
+    Driver::new().poll_stream_batch(stream, max, poll_fns)
+}
+
+/// Polls a stream's components for up to `max` items, adding them to `buf`.
+///
+/// This behaves like [`poll_stream_batch`], except that items are added to
+/// a user-provided buffer as they are produced, and the number of items
+/// added is returned instead. Since the items are already in `buf`, none of
+/// them are lost if a component returns an error partway through a batch.
+///
+/// # Panics
+///
+/// Panics if `max` is zero, or if all component poll methods return
+/// `Ok(Async::NothingToDo)`.
+///
+/// # Errors
+///
+/// Returns the first error returned by any of the component poll methods.
+#[cfg(feature = "std")]
+pub fn poll_stream_batch_into<T, B, C, Item, Error>(
+    stream: &mut T,
+    buf: &mut B,
+    max: usize,
+    poll_fns: &[C],
+) -> futures::Poll<Option<usize>, Error>
+where
+    B: Extend<Item>,
+    C: Component<T, Option<Item>, Error>,
+{
+    Driver::new().poll_stream_batch_into(stream, buf, max, poll_fns)
+}
+
+/// The body of a `futures::stream::Stream::poll` method, for components
+/// which can emit several items at once.
+///
+/// This behaves like [`poll_stream_with`], with `queue` as the context.
+/// Components can [`emit`](queue/struct.Queue.html#method.emit) items into
+/// the queue in addition to (or instead of) returning
+/// `Ok(Async::Ready(Some(item)))`. Whenever the queue isn't empty, the next
+/// item in it is returned without polling any components, and as soon as a
+/// component emits an item, the driver stops polling components and returns
+/// it. Items are returned in the order they were emitted or returned.
+///
+/// If a component returns `Ok(Async::Ready(None))` while there are still
+/// items in the queue, the queued items are returned first, so components
+/// must keep returning `Ok(Async::Ready(None))` once the stream has ended.
+///
+/// # Panics
+///
+/// Panics if all component poll methods return `Ok(Async::NothingToDo)`.
+///
+/// # Errors
+///
+/// Returns the first error returned by any of the component poll methods.
+#[cfg(feature = "std")]
+pub fn poll_stream_queued<T, C, Item, Error>(
+    stream: &mut T,
+    queue: &mut queue::Queue<Item>,
+    poll_fns: &[C],
+) -> futures::Poll<Option<Item>, Error>
+where
+    C: ComponentWith<T, queue::Queue<Item>, Option<Item>, Error>,
+{
+    Driver::new().poll_stream_queued(stream, queue, poll_fns)
+}
+
+/// The body of a `futures::future::Future::poll` method, for futures whose
+/// components are only polled while their guards allow it.
+///
+/// This behaves like [`poll_future`], except that each component is paired
+/// with a guard function. Before each call to a component, its guard is
+/// called with the future, and if it returns false, the component is
+/// skipped and treated as though it had returned `Ok(Async::NothingToDo)`.
+/// This replaces the early `return Ok(Async::NothingToDo)` checks which
+/// components often start with in order to run in sequence.
+///
+/// # Panics
+///
+/// Panics if all component poll methods return `Ok(Async::NothingToDo)` or
+/// are disabled by their guards. The panic message is an
+/// [`Idle`](diagnostics/struct.Idle.html) report, which lists the disabled
+/// components separately.
+///
+/// # Errors
+///
+/// Returns the first error returned by any of the component poll methods.
+#[cfg(feature = "std")]
+pub fn poll_future_guarded<T, C, Item, Error>(
+    future: &mut T,
+    poll_fns: &[(fn(&T) -> bool, C)],
+) -> futures::Poll<Item, Error>
+where
+    T: futures::future::Future<Item = Item, Error = Error>,
+    C: Component<T, Item, Error>,
+{
+    Driver::new().poll_future_guarded(future, poll_fns)
+}
+
+/// The body of a `futures::stream::Stream::poll` method, for streams whose
+/// components are only polled while their guards allow it.
+///
+/// See [`poll_future_guarded`] for details.
+///
+/// # Panics
+///
+/// Panics if all component poll methods return `Ok(Async::NothingToDo)` or
+/// are disabled by their guards.
+///
+/// # Errors
+///
+/// Returns the first error returned by any of the component poll methods.
+#[cfg(feature = "std")]
+pub fn poll_stream_guarded<T, C, Item, Error>(
+    stream: &mut T,
+    poll_fns: &[(fn(&T) -> bool, C)],
+) -> futures::Poll<Option<Item>, Error>
+where
+    T: futures::stream::Stream<Item = Item, Error = Error>,
+    C: Component<T, Option<Item>, Error>,
+{
+    Driver::new().poll_stream_guarded(stream, poll_fns)
+}
+
+/// The body of a `futures::future::Future::poll` method, for futures whose
+/// components can be enabled and disabled at runtime.
+///
+/// This behaves like [`poll_future`], except that components which are
+/// disabled in the [`Mask`](mask/struct.Mask.html) returned by `mask` are
+/// skipped, and treated as though they had returned
+/// `Ok(Async::NothingToDo)`. Changes to the mask take effect at the start of
+/// the next pass over the components, and the driver runs another pass
+/// whenever the mask was changed during the previous one.
+///
+/// # Panics
+///
+/// Panics if the mask doesn't have one entry per component, or if all
+/// component poll methods return `Ok(Async::NothingToDo)` or are disabled.
+///
+/// # Errors
+///
+/// Returns the first error returned by any of the component poll methods.
+#[cfg(feature = "std")]
+pub fn poll_future_masked<T, C, Item, Error>(
+    future: &mut T,
+    mask: fn(&mut T) -> &mut mask::Mask,
+    poll_fns: &[C],
+) -> futures::Poll<Item, Error>
+where
+    T: futures::future::Future<Item = Item, Error = Error>,
+    C: Component<T, Item, Error>,
+{
+    Driver::new().poll_future_masked(future, mask, poll_fns)
+}
+
+/// The body of a `futures::stream::Stream::poll` method, for streams whose
+/// components can be enabled and disabled at runtime.
+///
+/// See [`poll_future_masked`] for details.
+///
+/// # Panics
+///
+/// Panics if the mask doesn't have one entry per component, or if all
+/// component poll methods return `Ok(Async::NothingToDo)` or are disabled.
+///
+/// # Errors
+///
+/// Returns the first error returned by any of the component poll methods.
+#[cfg(feature = "std")]
+pub fn poll_stream_masked<T, C, Item, Error>(
+    stream: &mut T,
+    mask: fn(&mut T) -> &mut mask::Mask,
+    poll_fns: &[C],
+) -> futures::Poll<Option<Item>, Error>
+where
+    T: futures::stream::Stream<Item = Item, Error = Error>,
+    C: Component<T, Option<Item>, Error>,
+{
+    Driver::new().poll_stream_masked(stream, mask, poll_fns)
+}
+
+/// The body of a `futures::future::Future::poll` method, for futures whose
+/// components run in sequential phases.
+///
+/// This behaves like [`poll_future`], except that the components are
+/// grouped into an ordered list of phases, and only the components of the
+/// current phase (as recorded in the [`Phases`](phases/struct.Phases.html)
+/// returned by `phases`) are polled. Once all of them return
+/// `Ok(Async::NothingToDo)` during the same pass, the driver moves on to the
+/// next phase, and keeps going. The last phase is expected to eventually
+/// return a value.
+///
+/// For components of a phased future, `NothingToDo` means that the
+/// component is finished, not just idle: a phase is never revisited, so a
+/// component which is waiting on state which will be set elsewhere (by a
+/// component in a later phase, or by something outside of the driver)
+/// should return `NotReady` (after arranging for the task to be notified)
+/// rather than `NothingToDo`, or it will be skipped.
+///
+/// # Panics
+///
+/// Panics if all component poll methods of the last phase return
+/// `Ok(Async::NothingToDo)`, or if there are no phases.
+///
+/// # Errors
+///
+/// Returns the first error returned by any of the component poll methods.
+#[cfg(feature = "std")]
+pub fn poll_future_phased<T, C, Item, Error>(
+    future: &mut T,
+    phases: fn(&mut T) -> &mut phases::Phases,
+    poll_fns: &[&[C]],
+) -> futures::Poll<Item, Error>
+where
+    T: futures::future::Future<Item = Item, Error = Error>,
+    C: Component<T, Item, Error>,
+{
+    Driver::new().poll_future_phased(future, phases, poll_fns)
+}
+
+/// The body of a `futures::stream::Stream::poll` method, for streams whose
+/// components run in sequential phases.
+///
+/// See [`poll_future_phased`] for details.
+///
+/// # Panics
+///
+/// Panics if all component poll methods of the last phase return
+/// `Ok(Async::NothingToDo)`, or if there are no phases.
+///
+/// # Errors
+///
+/// Returns the first error returned by any of the component poll methods.
+#[cfg(feature = "std")]
+pub fn poll_stream_phased<T, C, Item, Error>(
+    stream: &mut T,
+    phases: fn(&mut T) -> &mut phases::Phases,
+    poll_fns: &[&[C]],
+) -> futures::Poll<Option<Item>, Error>
+where
+    T: futures::stream::Stream<Item = Item, Error = Error>,
+    C: Component<T, Option<Item>, Error>,
+{
+    Driver::new().poll_stream_phased(stream, phases, poll_fns)
+}
+
+/// The body of a `futures::future::Future::poll` method, for futures which
+/// should fail if they take too long.
+///
+/// This behaves like [`poll_future`], except that if the future hasn't
+/// produced a value by the time the [`Deadline`](timeout/struct.Deadline.html)
+/// returned by `deadline` expires, it returns the error created by the
+/// deadline instead. The error constructor is passed an
+/// [`Expired`](diagnostics/struct.Expired.html) report, which contains the
+/// components which were still waiting on inner futures or streams.
+///
+/// # Panics
+///
+/// Panics if all component poll methods return `Ok(Async::NothingToDo)`.
+///
+/// # Errors
+///
+/// Returns the first error returned by any of the component poll methods,
+/// or the error created by the deadline if it expires.
+#[cfg(feature = "std")]
+pub fn poll_future_until<T, Tm, C, Item, Error>(
+    future: &mut T,
+    deadline: fn(&mut T) -> &mut timeout::Deadline<Tm, Error>,
+    poll_fns: &[C],
+) -> futures::Poll<Item, Error>
+where
+    T: futures::future::Future<Item = Item, Error = Error>,
+    Tm: timeout::Timer,
+    C: Component<T, Item, Error>,
+{
+    Driver::new().poll_future_until(future, deadline, poll_fns)
+}
+
+/// The body of a `futures::stream::Stream::poll` method, for streams which
+/// should fail if they take too long.
+///
+/// This behaves like [`poll_future_until`], except for streams. With
+/// [`Deadline::idle`](timeout/struct.Deadline.html#method.idle), the
+/// deadline restarts each time the stream produces an item, so it limits
+/// the time between items rather than the lifetime of the whole stream.
+///
+/// # Panics
+///
+/// Panics if all component poll methods return `Ok(Async::NothingToDo)`.
+///
+/// # Errors
+///
+/// Returns the first error returned by any of the component poll methods,
+/// or the error created by the deadline if it expires.
+#[cfg(feature = "std")]
+pub fn poll_stream_until<T, Tm, C, Item, Error>(
+    stream: &mut T,
+    deadline: fn(&mut T) -> &mut timeout::Deadline<Tm, Error>,
+    poll_fns: &[C],
+) -> futures::Poll<Option<Item>, Error>
+where
+    T: futures::stream::Stream<Item = Item, Error = Error>,
+    Tm: timeout::Timer,
+    C: Component<T, Option<Item>, Error>,
+{
+    Driver::new().poll_stream_until(stream, deadline, poll_fns)
+}
+
+/// The body of a `futures::stream::Stream::poll` method, for streams which
+/// can be shut down gracefully.
+///
+/// This behaves like [`poll_stream`], except that each component is paired
+/// with a [`Role`](shutdown/enum.Role.html), and the stream can be shut down
+/// by triggering the [`Signal`](shutdown/struct.Signal.html) returned by
+/// `signal`. Once it has been triggered, `Role::Intake` components are
+/// skipped, `Role::Drain` components continue to be polled as usual, and
+/// the stream returns `Ready(None)` as soon as every drain component returns
+/// `Ok(Async::NothingToDo)` during the same pass.
+///
+/// # Panics
+///
+/// Panics if all component poll methods return `Ok(Async::NothingToDo)`
+/// before shutdown has been triggered.
+///
+/// # Errors
+///
+/// Returns the first error returned by any of the component poll methods.
+#[cfg(feature = "std")]
+pub fn poll_stream_shutdown<T, C, Item, Error>(
+    stream: &mut T,
+    signal: fn(&T) -> &shutdown::Signal,
+    poll_fns: &[(shutdown::Role, C)],
+) -> futures::Poll<Option<Item>, Error>
+where
+    T: futures::stream::Stream<Item = Item, Error = Error>,
+    C: Component<T, Option<Item>, Error>,
+{
+    Driver::new().poll_stream_shutdown(stream, signal, poll_fns)
+}
+
+/// The body of a `futures::future::Future::poll` method, for components
+/// which also take a shared context argument.
+///
+/// This behaves like [`poll_future`], except that each component is also
+/// passed `ctx`. Data which many components need to read (configuration, a
+/// clock, a metrics handle, etc) can be stored in the context rather than
+/// alongside the component state, which avoids conflicting borrows. Since
+/// the context is a separate argument, the component state is typically a
+/// field of the outer future rather than the outer future itself.
+///
+/// # Panics
+///
+/// Panics if all component poll methods return `Ok(Async::NothingToDo)`.
+///
+/// # Errors
+///
+/// Returns the first error returned by any of the component poll methods.
+///
+/// # Examples
+///
+/// ```
+/// # use futures::future::Future;
+/// struct Config {
+///     // ...
+/// }
+///
+/// struct State {
+///     // ...
+/// }
+///
+/// impl State {
+///     const POLL_FNS: &'static [&'static dyn for<'a, 'b> Fn(
+///         &'a mut Self,
+///         &'b mut Config,
+///     )
+///         -> component_future::Poll<(), ()>] = &[];
+/// }
+///
+/// struct Foo {
+///     state: State,
+///     config: Config,
+/// }
+///
+/// impl Future for Foo {
+///     type Item = ();
+///     type Error = ();
+///
+///     fn poll(&mut self) -> futures::Poll<Self::Item, Self::Error> {
+///         component_future::poll_future_with(
+///             &mut self.state,
+///             &mut self.config,
+///             State::POLL_FNS,
+///         )
+///     }
+/// }
+/// ```
+#[cfg(feature = "std")]
+pub fn poll_future_with<T, Ctx, C, Item, Error>(
+    future: &mut T,
+    ctx: &mut Ctx,
+    poll_fns: &[C],
+) -> futures::Poll<Item, Error>
+where
+    C: ComponentWith<T, Ctx, Item, Error>,
+{
+    Driver::new().poll_future_with(future, ctx, poll_fns)
+}
+
+/// The body of a `futures::stream::Stream::poll` method, for components
+/// which also take a shared context argument.
+///
+/// This behaves like [`poll_stream`], except that each component is also
+/// passed `ctx`. See [`poll_future_with`] for more details.
+///
+/// # Panics
+///
+/// Panics if all component poll methods return `Ok(Async::NothingToDo)`.
+///
+/// # Errors
+///
+/// Returns the first error returned by any of the component poll methods.
+#[cfg(feature = "std")]
+pub fn poll_stream_with<T, Ctx, C, Item, Error>(
+    stream: &mut T,
+    ctx: &mut Ctx,
+    poll_fns: &[C],
+) -> futures::Poll<Option<Item>, Error>
+where
+    C: ComponentWith<T, Ctx, Option<Item>, Error>,
+{
+    Driver::new().poll_stream_with(stream, ctx, poll_fns)
 }
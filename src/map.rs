@@ -0,0 +1,119 @@
+//! Components whose item and error types differ from those of the outer
+//! future or stream.
+//!
+//! Every component in a `POLL_FNS` slice has to return the same item and
+//! error types as the outer future or stream. A [`Map`] wraps a component
+//! with its own item and error types along with functions which convert
+//! them into the outer types, so that (for instance) a stream which merges
+//! requests and timer ticks into a single event enum doesn't need each
+//! component to do the conversion itself.
+//!
+//! # Examples
+//!
+//! ```
+//! use component_future::map::Map;
+//!
+//! enum Event {
+//!     Request(String),
+//!     Tick,
+//! }
+//!
+//! struct Server {
+//!     // ...
+//! }
+//!
+//! impl Server {
+//!     const POLL_FNS: &'static [&'static dyn component_future::Component<
+//!         Self,
+//!         Option<Event>,
+//!         String,
+//!     >] = &[
+//!         &Map::new(
+//!             &Self::poll_request,
+//!             |req: Option<String>| req.map(Event::Request),
+//!             |e: std::io::Error| e.to_string(),
+//!         ),
+//!         &Map::new(
+//!             &Self::poll_tick,
+//!             |tick: Option<()>| tick.map(|()| Event::Tick),
+//!             |e: String| e,
+//!         ),
+//!     ];
+//!
+//!     fn poll_request(
+//!         &mut self,
+//!     ) -> component_future::Poll<Option<String>, std::io::Error> {
+//!         // ...
+//! #       Ok(component_future::Async::NothingToDo)
+//!     }
+//!
+//!     fn poll_tick(&mut self) -> component_future::Poll<Option<()>, String> {
+//!         // ...
+//! #       Ok(component_future::Async::NothingToDo)
+//!     }
+//! }
+//! ```
+
+use crate::{Component, ComponentWith, Poll};
+
+/// A component which converts the item and error types of `component`
+/// with the given functions.
+pub struct Map<C, I, E, Item, Error> {
+    component: C,
+    item: fn(I) -> Item,
+    err: fn(E) -> Error,
+}
+
+impl<C, I, E, Item, Error> Map<C, I, E, Item, Error> {
+    /// Creates a component which runs `component`, converting its ready
+    /// values with `map_item` and its errors with `map_err`.
+    pub const fn new(
+        component: C,
+        map_item: fn(I) -> Item,
+        map_err: fn(E) -> Error,
+    ) -> Self {
+        Self {
+            component,
+            item: map_item,
+            err: map_err,
+        }
+    }
+
+    fn map(&self, res: Poll<I, E>) -> Poll<Item, Error> {
+        res.map(|res| res.map(self.item)).map_err(self.err)
+    }
+}
+
+impl<T, C, I, E, Item, Error> Component<T, Item, Error>
+    for Map<C, I, E, Item, Error>
+where
+    C: Component<T, I, E>,
+{
+    fn poll(&self, target: &mut T) -> Poll<Item, Error> {
+        self.map(self.component.poll(target))
+    }
+}
+
+impl<T, Ctx, C, I, E, Item, Error> ComponentWith<T, Ctx, Item, Error>
+    for Map<C, I, E, Item, Error>
+where
+    C: ComponentWith<T, Ctx, I, E>,
+{
+    fn poll(&self, target: &mut T, ctx: &mut Ctx) -> Poll<Item, Error> {
+        self.map(self.component.poll(target, ctx))
+    }
+}
+
+impl<T, C, I, E, Item, Error> crate::task::Component<T, Item, Error>
+    for Map<C, I, E, Item, Error>
+where
+    C: crate::task::Component<T, I, E>,
+{
+    fn poll(
+        &self,
+        target: &mut T,
+        cx: &mut core::task::Context<'_>,
+    ) -> Poll<Item, Error> {
+        self.map(self.component.poll(target, cx))
+    }
+}
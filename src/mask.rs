@@ -0,0 +1,173 @@
+//! Enabling and disabling components at runtime.
+//!
+//! Some components should only run while the outer future or stream is in a
+//! particular mode (for instance, a replication component which should only
+//! run while the node is the leader). With
+//! [`poll_future_masked`](../fn.poll_future_masked.html) and
+//! [`poll_stream_masked`](../fn.poll_stream_masked.html), the driver
+//! consults a [`Mask`] stored on the outer future or stream before each
+//! component is polled, and skips the components which are disabled.
+//!
+//! Changes to the mask made during a pass over the components (for
+//! instance, by one of the components) don't affect the rest of that pass,
+//! but always take effect on the next one: if the mask was changed, the
+//! driver runs another pass even if no component reported doing any work.
+//!
+//! # Examples
+//!
+//! ```
+//! use component_future::mask::Mask;
+//!
+//! struct Node {
+//!     mask: Mask,
+//!     // ...
+//! }
+//!
+//! impl Node {
+//!     const POLL_FNS: &'static [&'static dyn component_future::Component<
+//!         Self,
+//!         (),
+//!         (),
+//!     >] = &[&Self::poll_election, &Self::poll_replication];
+//!
+//!     fn new() -> Self {
+//!         let mut mask = Mask::named(&["election", "replication"]);
+//!         mask.set_named("replication", false);
+//!         Self { mask }
+//!     }
+//!
+//!     fn poll_election(&mut self) -> component_future::Poll<(), ()> {
+//!         // ...
+//!         # let won = true;
+//!         if won {
+//!             self.mask.set_named("replication", true);
+//!         }
+//!         // ...
+//! #       Ok(component_future::Async::NotReady)
+//!     }
+//!
+//!     fn poll_replication(&mut self) -> component_future::Poll<(), ()> {
+//!         // ...
+//! #       Ok(component_future::Async::NothingToDo)
+//!     }
+//! }
+//!
+//! impl futures::future::Future for Node {
+//!     type Item = ();
+//!     type Error = ();
+//!
+//!     fn poll(&mut self) -> futures::Poll<Self::Item, Self::Error> {
+//!         component_future::poll_future_masked(
+//!             self,
+//!             |node| &mut node.mask,
+//!             Self::POLL_FNS,
+//!         )
+//!     }
+//! }
+//! ```
+
+/// The set of components which are currently enabled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mask {
+    names: &'static [&'static str],
+    active: Vec<bool>,
+    requested: Vec<bool>,
+}
+
+impl Mask {
+    /// Creates a mask for `len` components, all of which are enabled.
+    #[must_use]
+    pub fn new(len: usize) -> Self {
+        Self {
+            names: &[],
+            active: vec![true; len],
+            requested: vec![true; len],
+        }
+    }
+
+    /// Creates a mask for components with the given names (in the same
+    /// order as the components themselves), all of which are enabled.
+    #[must_use]
+    pub fn named(names: &'static [&'static str]) -> Self {
+        Self {
+            names,
+            ..Self::new(names.len())
+        }
+    }
+
+    /// Returns the number of components in the mask.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.requested.len()
+    }
+
+    /// Returns true if the mask has no components.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.requested.is_empty()
+    }
+
+    /// Returns true if the component at `index` is enabled, including any
+    /// changes which haven't taken effect yet.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of range.
+    #[must_use]
+    pub fn is_enabled(&self, index: usize) -> bool {
+        self.requested[index]
+    }
+
+    /// Enables or disables the component at `index`, starting with the next
+    /// pass over the components.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of range.
+    pub fn set(&mut self, index: usize, enabled: bool) {
+        self.requested[index] = enabled;
+    }
+
+    /// Enables or disables the component called `name`, starting with the
+    /// next pass over the components.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no component is called `name`.
+    pub fn set_named(&mut self, name: &str, enabled: bool) {
+        let index = self.index_of(name);
+        self.set(index, enabled);
+    }
+
+    /// Returns true if the component called `name` is enabled, including
+    /// any changes which haven't taken effect yet.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no component is called `name`.
+    #[must_use]
+    pub fn is_enabled_named(&self, name: &str) -> bool {
+        self.is_enabled(self.index_of(name))
+    }
+
+    fn index_of(&self, name: &str) -> usize {
+        self.names
+            .iter()
+            .position(|&n| n == name)
+            .unwrap_or_else(|| panic!("no component named {:?}", name))
+    }
+
+    // makes the requested changes visible to the driver, at the start of a
+    // pass
+    pub(crate) fn apply(&mut self) {
+        self.active.clone_from(&self.requested);
+    }
+
+    pub(crate) fn is_active(&self, index: usize) -> bool {
+        self.active[index]
+    }
+
+    pub(crate) fn is_changed(&self) -> bool {
+        self.active != self.requested
+    }
+}
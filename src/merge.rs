@@ -0,0 +1,165 @@
+//! A stream which merges several inner streams of different types.
+//!
+//! [`Merge`] wraps each of its inner streams as a component, and runs them
+//! with [`poll_stream`](../fn.poll_stream.html). Items are tagged with
+//! [`Either`] to indicate which stream they came from, and the merged
+//! stream ends once all of the inner streams have ended. More
+//! than two streams can be merged by calling [`and`](Merge::and), which
+//! nests the tags (so merging `a`, `b`, and `c` produces items of type
+//! `Either<Either<A, B>, C>`).
+//!
+//! # Examples
+//!
+//! ```
+//! use component_future::merge::{Either, Merge};
+//! use futures::future::Future as _;
+//! use futures::stream::Stream as _;
+//!
+//! let numbers = futures::stream::iter_ok::<_, ()>(vec![1, 2]);
+//! let words = futures::stream::iter_ok(vec!["a", "b"]);
+//! let merged = Merge::new(numbers, words).collect().wait();
+//! assert_eq!(
+//!     merged,
+//!     Ok(vec![
+//!         Either::Left(1),
+//!         Either::Right("a"),
+//!         Either::Left(2),
+//!         Either::Right("b"),
+//!     ])
+//! );
+//! ```
+
+/// Determines which inner stream is polled first when more than one of
+/// them has an item ready.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Bias {
+    /// Always poll the inner streams in the order they were added, so
+    /// earlier streams are preferred.
+    First,
+
+    /// Alternate which side of the merge is polled first each time an item
+    /// is returned, so that a busy stream can't starve the others. When more
+    /// than two streams are merged, this applies at each level of nesting,
+    /// so the most recently added stream gets half of the turns.
+    #[default]
+    RoundRobin,
+}
+
+/// An item from a [`Merge`], tagged with the stream it came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Either<L, R> {
+    /// An item from the first stream.
+    Left(L),
+
+    /// An item from the second stream.
+    Right(R),
+}
+
+/// A stream which merges two inner streams, tagging each item with the
+/// stream it came from.
+pub struct Merge<L, R> {
+    left: Option<L>,
+    right: Option<R>,
+    bias: Bias,
+    right_first: bool,
+}
+
+impl<L, R> Merge<L, R> {
+    /// Creates a stream which merges `left` and `right`, using the default
+    /// [`Bias`].
+    pub fn new(left: L, right: R) -> Self {
+        Self {
+            left: Some(left),
+            right: Some(right),
+            bias: Bias::default(),
+            right_first: false,
+        }
+    }
+
+    /// Sets which stream should be polled first when more than one has an
+    /// item ready.
+    ///
+    /// This only applies to the streams which have been merged so far,
+    /// although streams merged later with [`and`](Self::and) use the same
+    /// bias, so this should usually be called before `and`.
+    #[must_use]
+    pub const fn bias(mut self, bias: Bias) -> Self {
+        self.bias = bias;
+        self
+    }
+
+    /// Merges another stream into this one.
+    pub fn and<S>(self, stream: S) -> Merge<Self, S> {
+        let bias = self.bias;
+        Merge::new(self, stream).bias(bias)
+    }
+}
+
+impl<L, R> Merge<L, R>
+where
+    L: futures::stream::Stream,
+    R: futures::stream::Stream<Error = L::Error>,
+{
+    fn poll_left(
+        &mut self,
+    ) -> crate::Poll<Option<Either<L::Item, R::Item>>, L::Error> {
+        if let Some(left) = &mut self.left {
+            if let Some(item) = crate::try_ready!(left.poll()) {
+                self.right_first = self.bias == Bias::RoundRobin;
+                Ok(crate::Async::Ready(Some(Either::Left(item))))
+            } else {
+                self.left.take();
+                Ok(self.end_or_continue())
+            }
+        } else {
+            Ok(crate::Async::NothingToDo)
+        }
+    }
+
+    fn poll_right(
+        &mut self,
+    ) -> crate::Poll<Option<Either<L::Item, R::Item>>, L::Error> {
+        if let Some(right) = &mut self.right {
+            if let Some(item) = crate::try_ready!(right.poll()) {
+                self.right_first = false;
+                Ok(crate::Async::Ready(Some(Either::Right(item))))
+            } else {
+                self.right.take();
+                Ok(self.end_or_continue())
+            }
+        } else {
+            Ok(crate::Async::NothingToDo)
+        }
+    }
+
+    const fn end_or_continue<Item>(&self) -> crate::Async<Option<Item>> {
+        if self.left.is_none() && self.right.is_none() {
+            crate::Async::Ready(None)
+        } else {
+            crate::Async::DidWork
+        }
+    }
+}
+
+impl<L, R> futures::stream::Stream for Merge<L, R>
+where
+    L: futures::stream::Stream,
+    R: futures::stream::Stream<Error = L::Error>,
+{
+    type Item = Either<L::Item, R::Item>;
+    type Error = L::Error;
+
+    fn poll(&mut self) -> futures::Poll<Option<Self::Item>, Self::Error> {
+        if self.left.is_none() && self.right.is_none() {
+            return Ok(futures::Async::Ready(None));
+        }
+
+        let poll_fns: [fn(&mut Self) -> crate::Poll<_, _>; 2] =
+            if self.right_first {
+                [Self::poll_right, Self::poll_left]
+            } else {
+                [Self::poll_left, Self::poll_right]
+            };
+        crate::poll_stream(self, &poll_fns)
+    }
+}
@@ -0,0 +1,110 @@
+//! Futures and streams which run their components in sequential phases.
+//!
+//! Many futures implement a linear protocol (connect, then authenticate,
+//! then send a request, then wait for the response), which with a flat list
+//! of components requires each component to check whether the previous
+//! steps have finished before doing anything. With
+//! [`poll_future_phased`](../fn.poll_future_phased.html) and
+//! [`poll_stream_phased`](../fn.poll_stream_phased.html), the components are
+//! instead grouped into an ordered list of phases, and only the components
+//! of the current phase are polled. Once every component of the current
+//! phase returns `Async::NothingToDo` during the same pass, the phase is
+//! finished and the driver moves on to the next one. The current phase is
+//! stored in a [`Phases`] field on the outer future or stream.
+//!
+//! This means that `Async::NothingToDo` has a stronger meaning for phased
+//! components than usual: it says that the component is done for good,
+//! since the driver never goes back to an earlier phase. A component which
+//! is idle while it waits for something outside of its phase to change
+//! should return `Async::NotReady` instead, and make sure the task will be
+//! notified when the change happens.
+//!
+//! # Examples
+//!
+//! ```
+//! # use futures::future::Future as _;
+//! use component_future::phases::Phases;
+//!
+//! type Step = Box<dyn futures::future::Future<Item = u32, Error = ()>>;
+//!
+//! struct Handshake {
+//!     phases: Phases,
+//!     connect: Option<Step>,
+//!     login: Option<Step>,
+//!     session: u32,
+//! }
+//!
+//! impl Handshake {
+//!     const PHASES: &'static [&'static [&'static dyn for<'a> Fn(
+//!         &'a mut Self,
+//!     )
+//!         -> component_future::Poll<
+//!         u32,
+//!         (),
+//!     >]] = &[&[&Self::poll_connect], &[&Self::poll_login], &[
+//!         &Self::poll_return,
+//!     ]];
+//!
+//!     fn poll_connect(&mut self) -> component_future::Poll<u32, ()> {
+//!         if let Some(connect) = &mut self.connect {
+//!             component_future::try_ready!(connect.poll());
+//!             self.connect = None;
+//!             Ok(component_future::Async::DidWork)
+//!         } else {
+//!             Ok(component_future::Async::NothingToDo)
+//!         }
+//!     }
+//!
+//!     fn poll_login(&mut self) -> component_future::Poll<u32, ()> {
+//!         if let Some(login) = &mut self.login {
+//!             self.session = component_future::try_ready!(login.poll());
+//!             self.login = None;
+//!             Ok(component_future::Async::DidWork)
+//!         } else {
+//!             Ok(component_future::Async::NothingToDo)
+//!         }
+//!     }
+//!
+//!     fn poll_return(&mut self) -> component_future::Poll<u32, ()> {
+//!         Ok(component_future::Async::Ready(self.session))
+//!     }
+//! }
+//!
+//! impl futures::future::Future for Handshake {
+//!     type Item = u32;
+//!     type Error = ();
+//!
+//!     fn poll(&mut self) -> futures::Poll<Self::Item, Self::Error> {
+//!         component_future::poll_future_phased(
+//!             self,
+//!             |handshake| &mut handshake.phases,
+//!             Self::PHASES,
+//!         )
+//!     }
+//! }
+//! ```
+
+/// The current phase of a future or stream, which should be stored on the
+/// outer future or stream.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Phases {
+    current: usize,
+}
+
+impl Phases {
+    /// Creates a new state, starting with the first phase.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { current: 0 }
+    }
+
+    /// Returns the index of the phase which is currently running.
+    #[must_use]
+    pub const fn current(&self) -> usize {
+        self.current
+    }
+
+    pub(crate) const fn advance(&mut self) {
+        self.current += 1;
+    }
+}
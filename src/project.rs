@@ -0,0 +1,109 @@
+//! Components which operate on a single field of the outer future or
+//! stream.
+//!
+//! Component poll methods normally take `&mut self` for the whole outer
+//! future or stream, which makes it difficult to write reusable components
+//! that only care about one part of the state. A [`Project`] wraps a
+//! component which operates on some type `U` along with a function which
+//! borrows a `U` out of the outer type `T`, so the same component (for
+//! instance, one which drives an optional inner future to completion) can be
+//! used by several different outer types.
+//!
+//! # Examples
+//!
+//! ```
+//! use component_future::project::Project;
+//!
+//! struct Slot<F> {
+//!     fut: Option<F>,
+//! }
+//!
+//! impl<F: futures::future::Future> Slot<F> {
+//!     fn poll(&mut self) -> component_future::Poll<F::Item, F::Error> {
+//!         if let Some(fut) = &mut self.fut {
+//!             let val = component_future::try_ready!(fut.poll());
+//!             self.fut.take();
+//!             Ok(component_future::Async::Ready(val))
+//!         } else {
+//!             Ok(component_future::Async::NothingToDo)
+//!         }
+//!     }
+//! }
+//!
+//! type Boxed = Box<dyn futures::future::Future<Item = u32, Error = ()>>;
+//!
+//! struct Race {
+//!     left: Slot<Boxed>,
+//!     right: Slot<Boxed>,
+//! }
+//!
+//! impl Race {
+//!     const POLL_FNS: &'static [&'static dyn component_future::Component<
+//!         Self,
+//!         u32,
+//!         (),
+//!     >] = &[
+//!         &Project::new(|race: &mut Self| &mut race.left, &Slot::poll),
+//!         &Project::new(|race: &mut Self| &mut race.right, &Slot::poll),
+//!     ];
+//! }
+//!
+//! impl futures::future::Future for Race {
+//!     type Item = u32;
+//!     type Error = ();
+//!
+//!     fn poll(&mut self) -> futures::Poll<Self::Item, Self::Error> {
+//!         component_future::poll_future(self, Self::POLL_FNS)
+//!     }
+//! }
+//! ```
+
+use crate::{Component, ComponentWith, Poll};
+
+/// A component which runs `component` on the part of the outer future or
+/// stream returned by `lens`.
+pub struct Project<T, U, C> {
+    lens: fn(&mut T) -> &mut U,
+    component: C,
+}
+
+impl<T, U, C> Project<T, U, C> {
+    /// Creates a component which runs `component` against the value
+    /// returned by `lens`.
+    pub const fn new(lens: fn(&mut T) -> &mut U, component: C) -> Self {
+        Self { lens, component }
+    }
+}
+
+impl<T, U, C, Item, Error> Component<T, Item, Error> for Project<T, U, C>
+where
+    C: Component<U, Item, Error>,
+{
+    fn poll(&self, target: &mut T) -> Poll<Item, Error> {
+        self.component.poll((self.lens)(target))
+    }
+}
+
+impl<T, U, Ctx, C, Item, Error> ComponentWith<T, Ctx, Item, Error>
+    for Project<T, U, C>
+where
+    C: ComponentWith<U, Ctx, Item, Error>,
+{
+    fn poll(&self, target: &mut T, ctx: &mut Ctx) -> Poll<Item, Error> {
+        self.component.poll((self.lens)(target), ctx)
+    }
+}
+
+impl<T, U, C, Item, Error> crate::task::Component<T, Item, Error>
+    for Project<T, U, C>
+where
+    C: crate::task::Component<U, Item, Error>,
+{
+    fn poll(
+        &self,
+        target: &mut T,
+        cx: &mut core::task::Context<'_>,
+    ) -> Poll<Item, Error> {
+        self.component.poll((self.lens)(target), cx)
+    }
+}
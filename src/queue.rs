@@ -0,0 +1,96 @@
+//! An output queue for streams whose components can produce several items
+//! at once.
+//!
+//! A component can only return a single `Async::Ready(item)`, so a component
+//! which decodes several frames from one read would otherwise have to stash
+//! the extra frames itself. With
+//! [`poll_stream_queued`](../fn.poll_stream_queued.html), each component is
+//! passed a [`Queue`] which it can [`emit`](Queue::emit) any number of items
+//! into, and the driver returns the queued items (in order) before polling
+//! the components again.
+
+use std::collections::VecDeque;
+
+/// A queue of items waiting to be returned from a stream.
+#[derive(Debug, Clone)]
+pub struct Queue<Item> {
+    items: VecDeque<Item>,
+    capacity: Option<usize>,
+}
+
+impl<Item> Queue<Item> {
+    /// Creates an unbounded queue.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            items: VecDeque::new(),
+            capacity: None,
+        }
+    }
+
+    /// Creates a queue which can hold at most `capacity` items.
+    ///
+    /// Components should check [`remaining`](Self::remaining) before
+    /// producing more items, so that a slow consumer applies backpressure to
+    /// whatever the components are reading from.
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            items: VecDeque::with_capacity(capacity),
+            capacity: Some(capacity),
+        }
+    }
+
+    /// Adds an item to the end of the queue.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the queue is full.
+    pub fn emit(&mut self, item: Item) {
+        assert!(!self.is_full(), "emitted an item into a full queue");
+        self.items.push_back(item);
+    }
+
+    /// Returns the number of items which can be emitted before the queue is
+    /// full, or `None` if the queue is unbounded.
+    #[must_use]
+    pub fn remaining(&self) -> Option<usize> {
+        self.capacity
+            .map(|capacity| capacity.saturating_sub(self.items.len()))
+    }
+
+    /// Returns true if no more items can be emitted.
+    #[must_use]
+    pub fn is_full(&self) -> bool {
+        self.remaining() == Some(0)
+    }
+
+    /// Returns the number of items in the queue.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns true if the queue contains no items.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub(crate) fn pop(&mut self) -> Option<Item> {
+        self.items.pop_front()
+    }
+
+    // items returned directly by a component are queued behind any items
+    // which were emitted earlier, even if that goes over capacity, since the
+    // component has already produced them
+    pub(crate) fn push(&mut self, item: Item) {
+        self.items.push_back(item);
+    }
+}
+
+impl<Item> Default for Queue<Item> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
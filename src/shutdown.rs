@@ -0,0 +1,162 @@
+//! Graceful shutdown for streams built out of components.
+//!
+//! A server built on [`poll_stream`](../fn.poll_stream.html) typically has
+//! some components which accept new input, and others which process input
+//! which has already been accepted. With
+//! [`poll_stream_shutdown`](../fn.poll_stream_shutdown.html), each component
+//! is given a [`Role`]. Once the stream's [`Signal`] has been triggered,
+//! [`Role::Intake`] components are no longer polled, [`Role::Drain`]
+//! components keep running until they have nothing left to do, and then the
+//! stream ends.
+//!
+//! # Examples
+//!
+//! ```
+//! use component_future::shutdown::{Role, Signal};
+//!
+//! struct Server {
+//!     shutdown: Signal,
+//!     // ...
+//! }
+//!
+//! impl Server {
+//!     const POLL_FNS: &'static [(
+//!         Role,
+//!         &'static dyn component_future::Component<Self, Option<u32>, ()>,
+//!     )] = &[
+//!         (Role::Intake, &Self::poll_accept),
+//!         (Role::Drain, &Self::poll_process),
+//!     ];
+//!
+//!     fn poll_accept(&mut self) -> component_future::Poll<Option<u32>, ()> {
+//!         // ...
+//! #       Ok(component_future::Async::NotReady)
+//!     }
+//!
+//!     fn poll_process(&mut self) -> component_future::Poll<Option<u32>, ()> {
+//!         // ...
+//! #       Ok(component_future::Async::NothingToDo)
+//!     }
+//! }
+//!
+//! impl futures::stream::Stream for Server {
+//!     type Item = u32;
+//!     type Error = ();
+//!
+//!     fn poll(&mut self) -> futures::Poll<Option<Self::Item>, Self::Error> {
+//!         component_future::poll_stream_shutdown(
+//!             self,
+//!             |server| &server.shutdown,
+//!             Self::POLL_FNS,
+//!         )
+//!     }
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// How a component should behave once shutdown has been triggered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    /// The component accepts new input, and is no longer polled after
+    /// shutdown has been triggered.
+    Intake,
+
+    /// The component processes input which has already been accepted, and
+    /// keeps being polled after shutdown has been triggered. Once all drain
+    /// components return `Async::NothingToDo` during the same pass (which
+    /// includes components which have been
+    /// [retired](../supervise/enum.Policy.html#variant.Retire)), the stream
+    /// ends.
+    Drain,
+}
+
+/// A signal which tells one or more streams to shut down.
+///
+/// Clones of a signal share the same state, so one clone can be stored on
+/// each stream and another kept elsewhere to call
+/// [`trigger`](Self::trigger) on. Each clone remembers the task which most
+/// recently polled it, so every stream should have its own clone.
+#[derive(Debug)]
+pub struct Signal {
+    inner: Arc<Inner>,
+    id: usize,
+    task: Arc<futures::task::AtomicTask>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    triggered: AtomicBool,
+    next_id: AtomicUsize,
+    watchers: Mutex<HashMap<usize, Arc<futures::task::AtomicTask>>>,
+}
+
+impl Signal {
+    /// Creates a signal which hasn't been triggered.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::watch(Arc::default())
+    }
+
+    fn watch(inner: Arc<Inner>) -> Self {
+        let id = inner.next_id.fetch_add(1, Ordering::SeqCst);
+        let task = Arc::new(futures::task::AtomicTask::new());
+        inner.watchers.lock().unwrap().insert(id, Arc::clone(&task));
+        Self { inner, id, task }
+    }
+
+    /// Starts shutting down every stream using this signal, waking them up
+    /// if necessary.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a task waiting on the signal panicked.
+    pub fn trigger(&self) {
+        self.inner.triggered.store(true, Ordering::SeqCst);
+        for task in self.inner.watchers.lock().unwrap().values() {
+            task.notify();
+        }
+    }
+
+    /// Returns true if [`trigger`](Self::trigger) has been called.
+    #[must_use]
+    pub fn is_triggered(&self) -> bool {
+        self.inner.triggered.load(Ordering::SeqCst)
+    }
+
+    // returns whether the signal has been triggered, and if not, arranges
+    // for the current task to be notified when it is
+    pub(crate) fn poll_triggered(&self) -> bool {
+        if self.is_triggered() {
+            return true;
+        }
+
+        self.task.register();
+
+        // the signal may have been triggered after the first check but
+        // before the task was registered
+        self.is_triggered()
+    }
+}
+
+impl Default for Signal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clone for Signal {
+    fn clone(&self) -> Self {
+        Self::watch(Arc::clone(&self.inner))
+    }
+}
+
+impl Drop for Signal {
+    fn drop(&mut self) {
+        if let Ok(mut watchers) = self.inner.watchers.lock() {
+            watchers.remove(&self.id);
+        }
+    }
+}
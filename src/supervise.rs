@@ -0,0 +1,173 @@
+//! Restart policies for components which can fail.
+//!
+//! By default, an error returned by any component causes the whole outer
+//! future or stream to return that error. Wrapping a component in a
+//! [`Supervised`] allows choosing a different [`Policy`] for it instead, for
+//! instance restarting a client connection a limited number of times before
+//! giving up.
+//!
+//! Since the list of components is usually a `const`, the state that the
+//! supervisor needs to keep track of (how many times the component has been
+//! restarted, and whether it has been retired) is stored in a [`State`]
+//! field on the outer future or stream, which the supervisor accesses via
+//! the given accessor function.
+//!
+//! # Examples
+//!
+//! ```
+//! # use futures::future::Future as _;
+//! use component_future::supervise::{Policy, State, Supervised};
+//!
+//! struct Client {
+//!     upstream: Box<dyn futures::future::Future<Item = u32, Error = String>>,
+//!     upstream_state: State,
+//! }
+//!
+//! impl Client {
+//!     const POLL_FNS: &'static [&'static dyn component_future::Component<
+//!         Self,
+//!         u32,
+//!         String,
+//!     >] = &[&Supervised::new(
+//!         &Self::poll_upstream,
+//!         |client: &mut Self| &mut client.upstream_state,
+//!         Policy::Restart {
+//!             reset: &Self::reconnect,
+//!             max_restarts: 3,
+//!         },
+//!     )];
+//!
+//!     fn poll_upstream(&mut self) -> component_future::Poll<u32, String> {
+//!         let val = component_future::try_ready!(self.upstream.poll());
+//!         Ok(component_future::Async::Ready(val))
+//!     }
+//!
+//!     fn reconnect(&mut self) {
+//!         self.upstream = Box::new(futures::future::ok(1));
+//!     }
+//! }
+//!
+//! impl futures::future::Future for Client {
+//!     type Item = u32;
+//!     type Error = String;
+//!
+//!     fn poll(&mut self) -> futures::Poll<Self::Item, Self::Error> {
+//!         component_future::poll_future(self, Self::POLL_FNS)
+//!     }
+//! }
+//! ```
+
+use crate::{Async, Component, Poll};
+
+/// What to do when a supervised component returns an error.
+pub enum Policy<'a, T> {
+    /// Return the error from the outer future or stream, as if the component
+    /// wasn't supervised at all.
+    Propagate,
+
+    /// Drop the error and stop running the component. It will return
+    /// `Async::NothingToDo` for all future polls.
+    Retire,
+
+    /// Drop the error and call `reset` to put the component back into a
+    /// state where it can make progress again. Once the component has been
+    /// restarted `max_restarts` times, further errors are propagated.
+    Restart {
+        /// Resets the outer future or stream's state for this component.
+        reset: &'a dyn Fn(&mut T),
+
+        /// The maximum number of times the component will be restarted.
+        max_restarts: usize,
+    },
+}
+
+/// The supervision state for a single component, which should be stored on
+/// the outer future or stream.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct State {
+    restarts: usize,
+    retired: bool,
+}
+
+impl State {
+    /// Creates a new state for a component which hasn't yet failed.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            restarts: 0,
+            retired: false,
+        }
+    }
+
+    /// Returns the number of times the component has been restarted.
+    #[must_use]
+    pub const fn restarts(&self) -> usize {
+        self.restarts
+    }
+
+    /// Returns true if the component has been retired.
+    #[must_use]
+    pub const fn is_retired(&self) -> bool {
+        self.retired
+    }
+}
+
+/// A component which handles its errors according to a [`Policy`].
+pub struct Supervised<'a, T, C> {
+    component: C,
+    state: fn(&mut T) -> &mut State,
+    policy: Policy<'a, T>,
+}
+
+impl<'a, T, C> Supervised<'a, T, C> {
+    /// Wraps `component` so that its errors are handled according to
+    /// `policy`. `state` should return the [`State`] field on the outer
+    /// future or stream which is dedicated to this component.
+    pub const fn new(
+        component: C,
+        state: fn(&mut T) -> &mut State,
+        policy: Policy<'a, T>,
+    ) -> Self {
+        Self {
+            component,
+            state,
+            policy,
+        }
+    }
+}
+
+impl<T, C, Item, Error> Component<T, Item, Error> for Supervised<'_, T, C>
+where
+    C: Component<T, Item, Error>,
+{
+    fn poll(&self, target: &mut T) -> Poll<Item, Error> {
+        if (self.state)(target).retired {
+            return Ok(Async::NothingToDo);
+        }
+
+        let e = match self.component.poll(target) {
+            Ok(a) => return Ok(a),
+            Err(e) => e,
+        };
+
+        match &self.policy {
+            Policy::Propagate => Err(e),
+            Policy::Retire => {
+                (self.state)(target).retired = true;
+                Ok(Async::DidWork)
+            }
+            Policy::Restart {
+                reset,
+                max_restarts,
+            } => {
+                let state = (self.state)(target);
+                if state.restarts >= *max_restarts {
+                    return Err(e);
+                }
+                state.restarts += 1;
+                reset(target);
+                Ok(Async::DidWork)
+            }
+        }
+    }
+}
@@ -0,0 +1,366 @@
+//! A driver for `core::future::Future` implementations.
+//!
+//! This works the same way as the crate-level `poll_future` and
+//! `poll_stream` functions, except that components are also passed the
+//! `core::task::Context` for the current poll, so that they can poll
+//! `core::future::Future` values. It only depends on `core`, so it is
+//! available even when the `std` feature is disabled, and since the list
+//! of components is usually a `const` array, polling doesn't allocate.
+//!
+//! # Examples
+//!
+//! ```
+//! use core::future::Future;
+//! use core::pin::Pin;
+//! use core::task::Context;
+//!
+//! struct Double {
+//!     fut: core::future::Ready<Result<u32, ()>>,
+//! }
+//!
+//! impl Double {
+//!     const POLL_FNS: &'static [&'static dyn for<'a, 'b, 'c> Fn(
+//!         &'a mut Self,
+//!         &'b mut Context<'c>,
+//!     ) -> component_future::Poll<u32, ()>] = &[&Self::poll_fut];
+//!
+//!     fn poll_fut(
+//!         &mut self,
+//!         cx: &mut Context<'_>,
+//!     ) -> component_future::Poll<u32, ()> {
+//!         let val = component_future::try_ready_task!(
+//!             Pin::new(&mut self.fut).poll(cx)
+//!         );
+//!         Ok(component_future::Async::Ready(val * 2))
+//!     }
+//! }
+//!
+//! impl Future for Double {
+//!     type Output = Result<u32, ()>;
+//!
+//!     fn poll(
+//!         self: Pin<&mut Self>,
+//!         cx: &mut Context<'_>,
+//!     ) -> core::task::Poll<Self::Output> {
+//!         component_future::task::poll_future(
+//!             self.get_mut(),
+//!             cx,
+//!             Self::POLL_FNS,
+//!         )
+//!     }
+//! }
+//! ```
+
+use crate::{Async, ComponentWith, Poll};
+
+use core::pin::Pin;
+use core::task::Context;
+
+/// A single component of a `core::future::Future` or stream.
+///
+/// This is the equivalent of [`Component`](../trait.Component.html) for
+/// components which also need the `core::task::Context`, and is implemented
+/// for any function of the form
+/// `Fn(&mut T, &mut Context<'_>) -> component_future::Poll<Item, Error>`.
+pub trait Component<T, Item, Error> {
+    /// Runs this component against the outer future or stream.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the outer future or stream should return that
+    /// error.
+    fn poll(&self, target: &mut T, cx: &mut Context<'_>)
+        -> Poll<Item, Error>;
+}
+
+impl<T, Item, Error, F> Component<T, Item, Error> for F
+where
+    F: Fn(&mut T, &mut Context<'_>) -> Poll<Item, Error>,
+{
+    fn poll(
+        &self,
+        target: &mut T,
+        cx: &mut Context<'_>,
+    ) -> Poll<Item, Error> {
+        self(target, cx)
+    }
+}
+
+impl<'a, T, Item, Error> Component<T, Item, Error>
+    for &'a (dyn Component<T, Item, Error> + 'a)
+{
+    fn poll(
+        &self,
+        target: &mut T,
+        cx: &mut Context<'_>,
+    ) -> Poll<Item, Error> {
+        (**self).poll(target, cx)
+    }
+}
+
+/// The body of a `core::future::Future::poll` method, for a future whose
+/// output is a `Result`.
+///
+/// It will repeatedly call the given component poll functions until none of
+/// them returns `Ok(Async::Ready(t))`, `Ok(Async::DidWork)`, or `Err(e)` and
+/// at least one of them returns `Ok(Async::NotReady)`.
+///
+/// # Panics
+///
+/// Panics if all component poll methods return `Ok(Async::NothingToDo)`.
+pub fn poll_future<T, C, Item, Error>(
+    future: &mut T,
+    cx: &mut Context<'_>,
+    poll_fns: &[C],
+) -> core::task::Poll<Result<Item, Error>>
+where
+    T: core::future::Future<Output = Result<Item, Error>>,
+    C: Component<T, Item, Error>,
+{
+    let outcome = run(poll_fns.len(), |i| poll_fns[i].poll(future, cx));
+    outcome.finish(cx)
+}
+
+/// The body of a `Stream::poll_next` method, for a stream whose items are
+/// `Result`s (such as a `futures::stream::TryStream`).
+///
+/// It will repeatedly call the given component poll functions until none of
+/// them returns `Ok(Async::Ready(t))`, `Ok(Async::DidWork)`, or `Err(e)` and
+/// at least one of them returns `Ok(Async::NotReady)`.
+///
+/// # Panics
+///
+/// Panics if all component poll methods return `Ok(Async::NothingToDo)`.
+pub fn poll_stream<T, C, Item, Error>(
+    stream: &mut T,
+    cx: &mut Context<'_>,
+    poll_fns: &[C],
+) -> core::task::Poll<Option<Result<Item, Error>>>
+where
+    C: Component<T, Option<Item>, Error>,
+{
+    let outcome = run(poll_fns.len(), |i| poll_fns[i].poll(stream, cx));
+    outcome.finish(cx).map(Result::transpose)
+}
+
+/// A single component of a `core::future::Future` or stream which isn't
+/// `Unpin`.
+///
+/// This is the same as [`Component`], except that the outer future or
+/// stream is passed as `Pin<&mut T>`, so that components can use something
+/// like `pin-project` to poll pinned fields. It is implemented for any
+/// function of the form
+/// `Fn(Pin<&mut T>, &mut Context<'_>) -> component_future::Poll<Item, Error>`,
+/// which includes methods taking `self: Pin<&mut Self>`.
+pub trait PinnedComponent<T, Item, Error> {
+    /// Runs this component against the outer future or stream.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the outer future or stream should return that
+    /// error.
+    fn poll(
+        &self,
+        target: Pin<&mut T>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Item, Error>;
+}
+
+impl<T, Item, Error, F> PinnedComponent<T, Item, Error> for F
+where
+    F: Fn(Pin<&mut T>, &mut Context<'_>) -> Poll<Item, Error>,
+{
+    fn poll(
+        &self,
+        target: Pin<&mut T>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Item, Error> {
+        self(target, cx)
+    }
+}
+
+impl<'a, T, Item, Error> PinnedComponent<T, Item, Error>
+    for &'a (dyn PinnedComponent<T, Item, Error> + 'a)
+{
+    fn poll(
+        &self,
+        target: Pin<&mut T>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Item, Error> {
+        (**self).poll(target, cx)
+    }
+}
+
+/// The body of a `core::future::Future::poll` method, for a future which
+/// isn't `Unpin`.
+///
+/// This behaves like [`poll_future`], except that it takes the pinned
+/// `self` directly, and passes it on to each [`PinnedComponent`].
+///
+/// # Panics
+///
+/// Panics if all component poll methods return `Ok(Async::NothingToDo)`.
+pub fn poll_future_pinned<T, C, Item, Error>(
+    mut future: Pin<&mut T>,
+    cx: &mut Context<'_>,
+    poll_fns: &[C],
+) -> core::task::Poll<Result<Item, Error>>
+where
+    T: core::future::Future<Output = Result<Item, Error>>,
+    C: PinnedComponent<T, Item, Error>,
+{
+    let outcome =
+        run(poll_fns.len(), |i| poll_fns[i].poll(future.as_mut(), cx));
+    outcome.finish(cx)
+}
+
+/// The body of a `Stream::poll_next` method, for a stream which isn't
+/// `Unpin`.
+///
+/// This behaves like [`poll_stream`], except that it takes the pinned
+/// `self` directly, and passes it on to each [`PinnedComponent`].
+///
+/// # Panics
+///
+/// Panics if all component poll methods return `Ok(Async::NothingToDo)`.
+pub fn poll_stream_pinned<T, C, Item, Error>(
+    mut stream: Pin<&mut T>,
+    cx: &mut Context<'_>,
+    poll_fns: &[C],
+) -> core::task::Poll<Option<Result<Item, Error>>>
+where
+    C: PinnedComponent<T, Option<Item>, Error>,
+{
+    let outcome =
+        run(poll_fns.len(), |i| poll_fns[i].poll(stream.as_mut(), cx));
+    outcome.finish(cx).map(Result::transpose)
+}
+
+/// The shared context passed to each component by [`poll_future_with`] and
+/// [`poll_stream_with`].
+///
+/// This dereferences to the user-provided context, and also carries the
+/// `core::task::Context` for the current poll.
+pub struct WithContext<'a, 'b, Ctx> {
+    cx: &'a mut Context<'b>,
+    ctx: &'a mut Ctx,
+}
+
+impl<'b, Ctx> WithContext<'_, 'b, Ctx> {
+    /// Returns the `core::task::Context` for the current poll, for polling
+    /// inner futures and streams.
+    pub const fn task(&mut self) -> &mut Context<'b> {
+        self.cx
+    }
+}
+
+impl<Ctx> core::ops::Deref for WithContext<'_, '_, Ctx> {
+    type Target = Ctx;
+
+    fn deref(&self) -> &Self::Target {
+        self.ctx
+    }
+}
+
+impl<Ctx> core::ops::DerefMut for WithContext<'_, '_, Ctx> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.ctx
+    }
+}
+
+/// The body of a `core::future::Future::poll` method, for components which
+/// also take a shared context argument.
+///
+/// This behaves like [`poll_future`], except that each component is passed
+/// a [`WithContext`] containing both `ctx` and `cx`, rather than just `cx`.
+/// See the crate-level `poll_future_with` for more details.
+///
+/// # Panics
+///
+/// Panics if all component poll methods return `Ok(Async::NothingToDo)`.
+pub fn poll_future_with<'a, 'b, T, Ctx, C, Item, Error>(
+    future: &mut T,
+    ctx: &'a mut Ctx,
+    cx: &'a mut Context<'b>,
+    poll_fns: &[C],
+) -> core::task::Poll<Result<Item, Error>>
+where
+    C: ComponentWith<T, WithContext<'a, 'b, Ctx>, Item, Error>,
+{
+    let mut with = WithContext { cx, ctx };
+    let outcome =
+        run(poll_fns.len(), |i| poll_fns[i].poll(future, &mut with));
+    outcome.finish(with.cx)
+}
+
+/// The body of a `Stream::poll_next` method, for components which also take
+/// a shared context argument.
+///
+/// This behaves like [`poll_stream`], except that each component is passed
+/// a [`WithContext`] containing both `ctx` and `cx`, rather than just `cx`.
+/// See the crate-level `poll_future_with` for more details.
+///
+/// # Panics
+///
+/// Panics if all component poll methods return `Ok(Async::NothingToDo)`.
+pub fn poll_stream_with<'a, 'b, T, Ctx, C, Item, Error>(
+    stream: &mut T,
+    ctx: &'a mut Ctx,
+    cx: &'a mut Context<'b>,
+    poll_fns: &[C],
+) -> core::task::Poll<Option<Result<Item, Error>>>
+where
+    C: ComponentWith<T, WithContext<'a, 'b, Ctx>, Option<Item>, Error>,
+{
+    let mut with = WithContext { cx, ctx };
+    let outcome =
+        run(poll_fns.len(), |i| poll_fns[i].poll(stream, &mut with));
+    outcome.finish(with.cx).map(Result::transpose)
+}
+
+enum Outcome<T> {
+    Ready(T),
+    Pending,
+    Yield,
+}
+
+impl<T> Outcome<T> {
+    fn finish(self, cx: &Context<'_>) -> core::task::Poll<T> {
+        match self {
+            Self::Ready(t) => core::task::Poll::Ready(t),
+            Self::Pending => core::task::Poll::Pending,
+            Self::Yield => {
+                cx.waker().wake_by_ref();
+                core::task::Poll::Pending
+            }
+        }
+    }
+}
+
+fn run<Item, Error>(
+    len: usize,
+    mut poll_one: impl FnMut(usize) -> Poll<Item, Error>,
+) -> Outcome<Result<Item, Error>> {
+    loop {
+        let mut not_ready = false;
+        let mut did_work = false;
+
+        for i in 0..len {
+            match poll_one(i) {
+                Ok(Async::Ready(e)) => return Outcome::Ready(Ok(e)),
+                Ok(Async::NotReady | Async::Blocked) => not_ready = true,
+                Ok(Async::NothingToDo) => {}
+                Ok(Async::DidWork) => did_work = true,
+                Ok(Async::Yield) => return Outcome::Yield,
+                Err(e) => return Outcome::Ready(Err(e)),
+            }
+        }
+
+        if !did_work {
+            if not_ready {
+                return Outcome::Pending;
+            }
+            unreachable!()
+        }
+    }
+}
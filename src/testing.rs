@@ -0,0 +1,575 @@
+//! Utilities for testing futures and streams built out of components.
+//!
+//! Futures such as `futures::future::ok` are always ready, so tests which
+//! only use them never exercise the paths where a component returns
+//! `Async::NotReady`. [`MockFuture`] and [`MockStream`] instead return a
+//! scripted sequence of results, and [`Harness`] allows polling the outer
+//! future or stream one step at a time without running an executor.
+//!
+//! [`fuzz_future`] and [`fuzz_stream`] build on these to check that a future
+//! or stream behaves the same way regardless of the order its components are
+//! polled in and of when its inner futures and streams return `NotReady`.
+//!
+//! # Examples
+//!
+//! ```
+//! use component_future::testing::{Harness, MockFuture};
+//!
+//! let fut = MockFuture::<_, ()>::new(vec![
+//!     futures::Async::NotReady,
+//!     futures::Async::Ready(3),
+//! ]);
+//! let polls = fut.polls();
+//!
+//! let mut harness = Harness::new(fut);
+//! assert_eq!(harness.poll_future(), Ok(futures::Async::NotReady));
+//! assert!(harness.is_notified());
+//! assert_eq!(harness.poll_future(), Ok(futures::Async::Ready(3)));
+//! assert_eq!(polls.get(), 2);
+//! ```
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// A shared counter of the number of times a mock has been polled.
+///
+/// This can be retrieved before the mock is moved into the outer future or
+/// stream, and checked afterwards.
+#[derive(Clone, Debug, Default)]
+pub struct PollCount(Arc<AtomicUsize>);
+
+impl PollCount {
+    /// Returns the number of times the mock has been polled so far.
+    #[must_use]
+    pub fn get(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    fn incr(&self) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// A future which returns a scripted sequence of results.
+///
+/// Each poll returns the next step of the script and notifies the current
+/// task, so that executors will continue polling until the script reaches a
+/// final value.
+///
+/// # Panics
+///
+/// Panics if polled after the script has been exhausted.
+#[derive(Debug)]
+pub struct MockFuture<Item, Error> {
+    steps: VecDeque<futures::Poll<Item, Error>>,
+    polls: PollCount,
+}
+
+impl<Item, Error> MockFuture<Item, Error> {
+    /// Creates a mock future which returns each of `steps` in order.
+    pub fn new(
+        steps: impl IntoIterator<Item = futures::Async<Item>>,
+    ) -> Self {
+        Self::from_polls(steps.into_iter().map(Ok))
+    }
+
+    /// Creates a mock future which returns each of `steps` in order, which
+    /// may include errors.
+    pub fn from_polls(
+        steps: impl IntoIterator<Item = futures::Poll<Item, Error>>,
+    ) -> Self {
+        Self {
+            steps: steps.into_iter().collect(),
+            polls: PollCount::default(),
+        }
+    }
+
+    /// Returns a handle to the number of times this future has been polled.
+    #[must_use]
+    pub fn polls(&self) -> PollCount {
+        self.polls.clone()
+    }
+}
+
+impl<Item, Error> futures::future::Future for MockFuture<Item, Error> {
+    type Item = Item;
+    type Error = Error;
+
+    fn poll(&mut self) -> futures::Poll<Self::Item, Self::Error> {
+        self.polls.incr();
+        let step = self
+            .steps
+            .pop_front()
+            .expect("MockFuture polled after its script was exhausted");
+        futures::task::current().notify();
+        step
+    }
+}
+
+/// A stream which returns a scripted sequence of results.
+///
+/// Each poll returns the next step of the script and notifies the current
+/// task, so that executors will continue polling until the script reaches
+/// the end of the stream.
+///
+/// # Panics
+///
+/// Panics if polled after the script has been exhausted.
+#[derive(Debug)]
+pub struct MockStream<Item, Error> {
+    steps: VecDeque<futures::Poll<Option<Item>, Error>>,
+    polls: PollCount,
+}
+
+impl<Item, Error> MockStream<Item, Error> {
+    /// Creates a mock stream which returns each of `steps` in order.
+    pub fn new(
+        steps: impl IntoIterator<Item = futures::Async<Option<Item>>>,
+    ) -> Self {
+        Self::from_polls(steps.into_iter().map(Ok))
+    }
+
+    /// Creates a mock stream which returns each of `steps` in order, which
+    /// may include errors.
+    pub fn from_polls(
+        steps: impl IntoIterator<Item = futures::Poll<Option<Item>, Error>>,
+    ) -> Self {
+        Self {
+            steps: steps.into_iter().collect(),
+            polls: PollCount::default(),
+        }
+    }
+
+    /// Returns a handle to the number of times this stream has been polled.
+    #[must_use]
+    pub fn polls(&self) -> PollCount {
+        self.polls.clone()
+    }
+}
+
+impl<Item, Error> futures::stream::Stream for MockStream<Item, Error> {
+    type Item = Item;
+    type Error = Error;
+
+    fn poll(&mut self) -> futures::Poll<Option<Self::Item>, Self::Error> {
+        self.polls.incr();
+        let step = self
+            .steps
+            .pop_front()
+            .expect("MockStream polled after its script was exhausted");
+        futures::task::current().notify();
+        step
+    }
+}
+
+#[derive(Debug, Default)]
+struct Flag(AtomicBool);
+
+impl futures::executor::Notify for Flag {
+    fn notify(&self, _id: usize) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Polls a future or stream manually, keeping track of whether it has
+/// notified its task.
+pub struct Harness<T> {
+    spawn: futures::executor::Spawn<T>,
+    notified: Arc<Flag>,
+}
+
+impl<T> Harness<T> {
+    /// Wraps `t` in a new task.
+    pub fn new(t: T) -> Self {
+        Self {
+            spawn: futures::executor::spawn(t),
+            notified: Arc::new(Flag::default()),
+        }
+    }
+
+    /// Returns true if the task was notified during or since the most
+    /// recent poll.
+    #[must_use]
+    pub fn is_notified(&self) -> bool {
+        self.notified.0.load(Ordering::SeqCst)
+    }
+
+    /// Returns a reference to the wrapped future or stream.
+    pub fn get_ref(&self) -> &T {
+        self.spawn.get_ref()
+    }
+
+    /// Returns a mutable reference to the wrapped future or stream.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.spawn.get_mut()
+    }
+
+    /// Polls the wrapped future once.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error returned by the wrapped future.
+    pub fn poll_future(&mut self) -> futures::Poll<T::Item, T::Error>
+    where
+        T: futures::future::Future,
+    {
+        self.notified.0.store(false, Ordering::SeqCst);
+        self.spawn.poll_future_notify(&self.notified, 0)
+    }
+
+    /// Polls the wrapped stream once.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error returned by the wrapped stream.
+    pub fn poll_stream(&mut self) -> futures::Poll<Option<T::Item>, T::Error>
+    where
+        T: futures::stream::Stream,
+    {
+        self.notified.0.store(false, Ordering::SeqCst);
+        self.spawn.poll_stream_notify(&self.notified, 0)
+    }
+}
+
+/// A [`Timer`](../timeout/trait.Timer.html) which only advances when told
+/// to.
+///
+/// Instants are measured as the time elapsed since the clock was created.
+/// Clones of a clock share the same time, so a clone can be given to each
+/// [`timeout::State`](../timeout/struct.State.html) while the test keeps
+/// the original to call [`advance`](Self::advance) on.
+#[derive(Debug, Clone, Default)]
+pub struct VirtualClock(Arc<Mutex<ClockState>>);
+
+#[derive(Debug, Default)]
+struct ClockState {
+    now: Duration,
+    waiting: Vec<(Duration, futures::task::Task)>,
+}
+
+impl VirtualClock {
+    /// Creates a new clock, starting at zero.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Moves the clock forward by `duration`, notifying any tasks whose
+    /// deadlines have now passed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a task waiting on the clock panicked.
+    pub fn advance(&self, duration: Duration) {
+        let mut state = self.0.lock().unwrap();
+        state.now += duration;
+        let now = state.now;
+        state.waiting.retain(|(deadline, task)| {
+            if *deadline <= now {
+                task.notify();
+                false
+            } else {
+                true
+            }
+        });
+    }
+}
+
+impl crate::timeout::Timer for VirtualClock {
+    type Instant = Duration;
+
+    fn now(&self) -> Self::Instant {
+        self.0.lock().unwrap().now
+    }
+
+    fn notify_at(&self, deadline: Self::Instant) {
+        let mut state = self.0.lock().unwrap();
+        if deadline <= state.now {
+            futures::task::current().notify();
+        } else if !state.waiting.iter().any(|(waiting, task)| {
+            *waiting == deadline && task.will_notify_current()
+        }) {
+            state.waiting.push((deadline, futures::task::current()));
+        }
+    }
+}
+
+/// Source of randomness for [`fuzz_future`] and [`fuzz_stream`].
+///
+/// Each run of the fuzzer passes a `Fuzz` created from that run's seed to
+/// the function which constructs the outer future or stream, which should
+/// use it to construct its mocks. A failing run can be reproduced by
+/// creating a `Fuzz` with the reported seed.
+///
+/// Seed `0` is special: it keeps the declared order of the components and
+/// doesn't inject any `NotReady` values, and is used as the reference run
+/// which all of the other runs are compared against.
+#[derive(Debug, Clone)]
+pub struct Fuzz {
+    seed: u64,
+    state: u64,
+}
+
+impl Fuzz {
+    const MAX_INJECTED: u64 = 3;
+
+    /// Creates a new source of randomness from `seed`.
+    #[must_use]
+    pub const fn new(seed: u64) -> Self {
+        Self { seed, state: seed }
+    }
+
+    /// Returns the seed this was created with.
+    #[must_use]
+    pub const fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Returns a copy of `steps` with a random number of
+    /// `futures::Async::NotReady` values inserted before each step.
+    pub fn inject_not_ready<T>(
+        &mut self,
+        steps: impl IntoIterator<Item = futures::Async<T>>,
+    ) -> Vec<futures::Async<T>> {
+        let mut injected = vec![];
+        for step in steps {
+            if self.seed != 0 {
+                for _ in 0..self.below(Self::MAX_INJECTED + 1) {
+                    injected.push(futures::Async::NotReady);
+                }
+            }
+            injected.push(step);
+        }
+        injected
+    }
+
+    /// Creates a [`MockFuture`] which returns `steps`, with randomly
+    /// injected `NotReady` values.
+    pub fn mock_future<Item, Error>(
+        &mut self,
+        steps: impl IntoIterator<Item = futures::Async<Item>>,
+    ) -> MockFuture<Item, Error> {
+        MockFuture::new(self.inject_not_ready(steps))
+    }
+
+    /// Creates a [`MockStream`] which returns `steps`, with randomly
+    /// injected `NotReady` values.
+    pub fn mock_stream<Item, Error>(
+        &mut self,
+        steps: impl IntoIterator<Item = futures::Async<Option<Item>>>,
+    ) -> MockStream<Item, Error> {
+        MockStream::new(self.inject_not_ready(steps))
+    }
+
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        if self.seed == 0 {
+            return;
+        }
+        for i in (1..items.len()).rev() {
+            // j <= i, so this can't truncate
+            #[allow(clippy::cast_possible_truncation)]
+            let j = self.below(i as u64 + 1) as usize;
+            items.swap(i, j);
+        }
+    }
+
+    // splitmix64
+    const fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    const fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Outcome<Item, Error> {
+    Item(Item),
+    Error(Error),
+    Stalled,
+    Panicked(String),
+}
+
+const MAX_POLLS: usize = 10_000;
+
+struct Shuffled<'a, T, Item, Error> {
+    target: T,
+    poll_fns: Vec<&'a dyn crate::Component<T, Item, Error>>,
+}
+
+impl<'a, T, Item, Error> Shuffled<'a, T, Item, Error> {
+    fn new<C>(target: T, poll_fns: &'a [C], fuzz: &mut Fuzz) -> Self
+    where
+        C: crate::Component<T, Item, Error>,
+    {
+        let mut poll_fns: Vec<&'a dyn crate::Component<T, Item, Error>> =
+            poll_fns.iter().map(|c| c as _).collect();
+        fuzz.shuffle(&mut poll_fns);
+        Self { target, poll_fns }
+    }
+}
+
+impl<T> futures::future::Future for Shuffled<'_, T, T::Item, T::Error>
+where
+    T: futures::future::Future,
+{
+    type Item = T::Item;
+    type Error = T::Error;
+
+    fn poll(&mut self) -> futures::Poll<Self::Item, Self::Error> {
+        crate::poll_future(&mut self.target, &self.poll_fns)
+    }
+}
+
+impl<T> futures::stream::Stream for Shuffled<'_, T, Option<T::Item>, T::Error>
+where
+    T: futures::stream::Stream,
+{
+    type Item = T::Item;
+    type Error = T::Error;
+
+    fn poll(&mut self) -> futures::Poll<Option<Self::Item>, Self::Error> {
+        crate::poll_stream(&mut self.target, &self.poll_fns)
+    }
+}
+
+fn compare<Item, Error>(
+    mut run: impl FnMut(&mut Fuzz) -> Vec<Outcome<Item, Error>>,
+    runs: u64,
+) where
+    Item: std::fmt::Debug + PartialEq,
+    Error: std::fmt::Debug + PartialEq,
+{
+    let mut run_seed = |seed| {
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            run(&mut Fuzz::new(seed))
+        }))
+        .unwrap_or_else(|e| {
+            let msg = e
+                .downcast_ref::<&str>()
+                .map(|s| (*s).to_string())
+                .or_else(|| e.downcast_ref::<String>().cloned())
+                .unwrap_or_default();
+            vec![Outcome::Panicked(msg)]
+        })
+    };
+
+    let expected = run_seed(0);
+    for seed in 1..runs {
+        let got = run_seed(seed);
+        assert!(
+            got == expected,
+            "seed {} produced {:?}, but seed 0 produced {:?}",
+            seed,
+            got,
+            expected
+        );
+    }
+}
+
+/// Checks that a future produces the same result regardless of the order
+/// of its components.
+///
+/// For each seed in `0..runs`, this calls `make` to create the future, and
+/// then polls it to completion with its components in a random order. Any
+/// mocks created via the given [`Fuzz`] will additionally have random
+/// `NotReady` values injected. If the future returns `NotReady` without
+/// notifying its task, it is considered to have stalled, and panics (for
+/// instance, from all of the components returning `NothingToDo`) are also
+/// recorded as part of the result.
+///
+/// # Panics
+///
+/// Panics with the smallest failing seed if any run produces a different
+/// result from the run with seed `0`.
+pub fn fuzz_future<T, C, Item, Error>(
+    runs: u64,
+    mut make: impl FnMut(&mut Fuzz) -> T,
+    poll_fns: &[C],
+) where
+    T: futures::future::Future<Item = Item, Error = Error>,
+    C: crate::Component<T, Item, Error>,
+    Item: std::fmt::Debug + PartialEq,
+    Error: std::fmt::Debug + PartialEq,
+{
+    compare(
+        |fuzz| {
+            let target = make(fuzz);
+            let mut harness =
+                Harness::new(Shuffled::new(target, poll_fns, fuzz));
+            for _ in 0..MAX_POLLS {
+                match harness.poll_future() {
+                    Ok(futures::Async::Ready(item)) => {
+                        return vec![Outcome::Item(item)]
+                    }
+                    Ok(futures::Async::NotReady) => {
+                        if !harness.is_notified() {
+                            break;
+                        }
+                    }
+                    Err(e) => return vec![Outcome::Error(e)],
+                }
+            }
+            vec![Outcome::Stalled]
+        },
+        runs,
+    );
+}
+
+/// Checks that a stream produces the same items regardless of the order of
+/// its components.
+///
+/// This behaves like [`fuzz_future`], except that the stream is polled
+/// until it ends or returns an error, and the full sequence of items is
+/// compared.
+///
+/// # Panics
+///
+/// Panics with the smallest failing seed if any run produces a different
+/// result from the run with seed `0`.
+pub fn fuzz_stream<T, C, Item, Error>(
+    runs: u64,
+    mut make: impl FnMut(&mut Fuzz) -> T,
+    poll_fns: &[C],
+) where
+    T: futures::stream::Stream<Item = Item, Error = Error>,
+    C: crate::Component<T, Option<Item>, Error>,
+    Item: std::fmt::Debug + PartialEq,
+    Error: std::fmt::Debug + PartialEq,
+{
+    compare(
+        |fuzz| {
+            let target = make(fuzz);
+            let mut harness =
+                Harness::new(Shuffled::new(target, poll_fns, fuzz));
+            let mut outcomes = vec![];
+            for _ in 0..MAX_POLLS {
+                match harness.poll_stream() {
+                    Ok(futures::Async::Ready(Some(item))) => {
+                        outcomes.push(Outcome::Item(item));
+                    }
+                    Ok(futures::Async::Ready(None)) => return outcomes,
+                    Ok(futures::Async::NotReady) => {
+                        if !harness.is_notified() {
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        outcomes.push(Outcome::Error(e));
+                        return outcomes;
+                    }
+                }
+            }
+            outcomes.push(Outcome::Stalled);
+            outcomes
+        },
+        runs,
+    );
+}
@@ -0,0 +1,376 @@
+//! Timeouts for components which wait on inner futures or streams, and
+//! deadlines for whole futures and streams.
+//!
+//! Wrapping a component in a [`Timeout`] starts a timer whenever the
+//! component returns `Async::NotReady`. If the component is still returning
+//! `NotReady` once the timer expires, the given handler is called instead,
+//! which can reset the component's state and return `Async::DidWork` to try
+//! again, or return an error to fail the outer future or stream. Any other
+//! result from the component (including `Async::NothingToDo`) cancels the
+//! timer.
+//!
+//! Time is measured with a [`Timer`], so that tests can use a
+//! [`VirtualClock`](../testing/struct.VirtualClock.html) instead of waiting
+//! for real time to pass. As with
+//! [`supervise`](../supervise/index.html), the per-component state
+//! (including the timer) is stored in a [`State`] field on the outer future
+//! or stream.
+//!
+//! A [`Deadline`] instead applies to the outer future or stream as a whole,
+//! and is enforced by the driver (see
+//! [`poll_future_until`](../fn.poll_future_until.html) and
+//! [`poll_stream_until`](../fn.poll_stream_until.html)), so that the error
+//! can report which components were still pending.
+//!
+//! # Examples
+//!
+//! ```
+//! use component_future::timeout::{State, ThreadTimer, Timeout};
+//! use std::time::Duration;
+//!
+//! struct Client {
+//!     response: Box<dyn futures::future::Future<Item = u32, Error = String>>,
+//!     response_timeout: State<ThreadTimer>,
+//! }
+//!
+//! impl Client {
+//!     const POLL_FNS: &'static [&'static dyn component_future::Component<
+//!         Self,
+//!         u32,
+//!         String,
+//!     >] = &[&Timeout::new(
+//!         &Self::poll_response,
+//!         |client: &mut Self| &mut client.response_timeout,
+//!         Duration::from_secs(30),
+//!         &|_| Err("timed out waiting for a response".to_string()),
+//!     )];
+//!
+//!     fn poll_response(&mut self) -> component_future::Poll<u32, String> {
+//!         let val = component_future::try_ready!(self.response.poll());
+//!         Ok(component_future::Async::Ready(val))
+//!     }
+//! }
+//!
+//! impl futures::future::Future for Client {
+//!     type Item = u32;
+//!     type Error = String;
+//!
+//!     fn poll(&mut self) -> futures::Poll<Self::Item, Self::Error> {
+//!         component_future::poll_future(self, Self::POLL_FNS)
+//!     }
+//! }
+//! ```
+
+use crate::diagnostics::Expired;
+use crate::{Async, Component, Poll};
+
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+/// A source of time, which can wake up the current task at a given point in
+/// the future.
+pub trait Timer {
+    /// A point in time, as measured by this timer.
+    type Instant: Copy + Ord + std::ops::Add<Duration, Output = Self::Instant>;
+
+    /// Returns the current time.
+    fn now(&self) -> Self::Instant;
+
+    /// Arranges for the current task to be notified once `deadline` has
+    /// passed.
+    ///
+    /// This is only called again when the deadline or the task changes, so
+    /// the notification must happen even if the timer was previously asked
+    /// for a different deadline.
+    fn notify_at(&self, deadline: Self::Instant);
+}
+
+impl<Tm: Timer + ?Sized> Timer for &Tm {
+    type Instant = Tm::Instant;
+
+    fn now(&self) -> Self::Instant {
+        (**self).now()
+    }
+
+    fn notify_at(&self, deadline: Self::Instant) {
+        (**self).notify_at(deadline);
+    }
+}
+
+/// A [`Timer`] which uses the system clock, and spawns a thread which
+/// sleeps until the deadline to notify the current task.
+///
+/// Each timer keeps track of a single task and a single deadline, and
+/// asking for a different deadline moves the wakeup rather than adding
+/// another one, so each [`State`] or [`Deadline`] should have its own
+/// timer. At most one thread is running for each timer at a time. This
+/// doesn't depend on any particular executor, but a timer based on the
+/// executor's own timer facilities will be more efficient if timeouts are
+/// frequent.
+#[derive(Debug, Default)]
+pub struct ThreadTimer(Arc<Armed>);
+
+#[derive(Debug, Default)]
+struct Armed {
+    task: futures::task::AtomicTask,
+    wakeup: Mutex<Wakeup>,
+    changed: Condvar,
+}
+
+#[derive(Debug, Default)]
+struct Wakeup {
+    deadline: Option<std::time::Instant>,
+    sleeping: bool,
+}
+
+impl ThreadTimer {
+    /// Creates a timer which isn't waiting for any deadline yet.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Timer for ThreadTimer {
+    type Instant = std::time::Instant;
+
+    fn now(&self) -> Self::Instant {
+        std::time::Instant::now()
+    }
+
+    fn notify_at(&self, deadline: Self::Instant) {
+        self.0.task.register();
+
+        let mut wakeup = self.0.wakeup.lock().unwrap();
+        if wakeup.deadline == Some(deadline) {
+            return;
+        }
+        wakeup.deadline = Some(deadline);
+        if wakeup.sleeping {
+            // the sleeping thread will pick up the new deadline
+            self.0.changed.notify_one();
+            return;
+        }
+        wakeup.sleeping = true;
+        drop(wakeup);
+
+        let inner = Arc::clone(&self.0);
+        std::thread::spawn(move || {
+            let mut wakeup = inner.wakeup.lock().unwrap();
+            while let Some(deadline) = wakeup.deadline {
+                let now = std::time::Instant::now();
+                if deadline <= now {
+                    wakeup.deadline = None;
+                    break;
+                }
+                wakeup = inner
+                    .changed
+                    .wait_timeout(wakeup, deadline - now)
+                    .unwrap()
+                    .0;
+            }
+            wakeup.sleeping = false;
+            drop(wakeup);
+            inner.task.notify();
+        });
+    }
+}
+
+// returns whether `notify_at` needs to be called again for the current
+// deadline, which is only the case if it was last called from another task
+fn needs_arming(armed: &mut Option<futures::task::Task>) -> bool {
+    if let Some(task) = armed {
+        if task.will_notify_current() {
+            return false;
+        }
+    }
+    *armed = Some(futures::task::current());
+    true
+}
+
+/// The timeout state for a single component, which should be stored on the
+/// outer future or stream.
+#[derive(Debug, Clone)]
+pub struct State<Tm: Timer> {
+    timer: Tm,
+    deadline: Option<Tm::Instant>,
+    armed: Option<futures::task::Task>,
+    timeouts: usize,
+}
+
+impl<Tm: Timer> State<Tm> {
+    /// Creates a new state for a component which hasn't started waiting
+    /// yet, which will measure time using `timer`.
+    pub const fn new(timer: Tm) -> Self {
+        Self {
+            timer,
+            deadline: None,
+            armed: None,
+            timeouts: 0,
+        }
+    }
+
+    /// Returns the time at which the component will time out, if it is
+    /// currently waiting.
+    pub const fn deadline(&self) -> Option<Tm::Instant> {
+        self.deadline
+    }
+
+    /// Returns the number of times the component has timed out.
+    pub const fn timeouts(&self) -> usize {
+        self.timeouts
+    }
+}
+
+/// A component which calls a handler if it stays `NotReady` for too long.
+pub struct Timeout<'a, T, C, Tm: Timer, Item, Error> {
+    component: C,
+    state: fn(&mut T) -> &mut State<Tm>,
+    duration: Duration,
+    handler: &'a dyn Fn(&mut T) -> Poll<Item, Error>,
+}
+
+impl<'a, T, C, Tm: Timer, Item, Error> Timeout<'a, T, C, Tm, Item, Error> {
+    /// Wraps `component` so that `on_timeout` is called instead once it has
+    /// been returning `NotReady` for at least `duration`. `state` should
+    /// return the [`State`] field on the outer future or stream which is
+    /// dedicated to this component.
+    pub const fn new(
+        component: C,
+        state: fn(&mut T) -> &mut State<Tm>,
+        duration: Duration,
+        on_timeout: &'a dyn Fn(&mut T) -> Poll<Item, Error>,
+    ) -> Self {
+        Self {
+            component,
+            state,
+            duration,
+            handler: on_timeout,
+        }
+    }
+}
+
+impl<T, C, Tm, Item, Error> Component<T, Item, Error>
+    for Timeout<'_, T, C, Tm, Item, Error>
+where
+    C: Component<T, Item, Error>,
+    Tm: Timer,
+{
+    fn poll(&self, target: &mut T) -> Poll<Item, Error> {
+        let res = self.component.poll(target);
+        let state = (self.state)(target);
+        if !matches!(res, Ok(Async::NotReady | Async::Blocked)) {
+            state.deadline = None;
+            state.armed = None;
+            return res;
+        }
+
+        let now = state.timer.now();
+        let deadline = if let Some(deadline) = state.deadline {
+            deadline
+        } else {
+            state.armed = None;
+            *state.deadline.insert(now + self.duration)
+        };
+        if now < deadline {
+            if needs_arming(&mut state.armed) {
+                state.timer.notify_at(deadline);
+            }
+            return res;
+        }
+
+        state.deadline = None;
+        state.armed = None;
+        state.timeouts += 1;
+        (self.handler)(target)
+    }
+}
+
+/// The deadline for an entire future or stream, which should be stored on
+/// the outer future or stream.
+pub struct Deadline<Tm: Timer, Error> {
+    timer: Tm,
+    duration: Duration,
+    idle: bool,
+    expires_at: Option<Tm::Instant>,
+    armed: Option<futures::task::Task>,
+    on_expired: fn(&Expired) -> Error,
+}
+
+impl<Tm: Timer, Error> Deadline<Tm, Error> {
+    /// Creates a deadline which expires `duration` after the outer future
+    /// or stream is first polled. If it hasn't finished by then,
+    /// `on_expired` is called to create the error to return.
+    pub const fn new(
+        timer: Tm,
+        duration: Duration,
+        on_expired: fn(&Expired) -> Error,
+    ) -> Self {
+        Self {
+            timer,
+            duration,
+            idle: false,
+            expires_at: None,
+            armed: None,
+            on_expired,
+        }
+    }
+
+    /// Creates a deadline which expires if the outer stream goes for
+    /// `duration` without producing an item. If it does, `on_expired` is
+    /// called to create the error to return.
+    pub const fn idle(
+        timer: Tm,
+        duration: Duration,
+        on_expired: fn(&Expired) -> Error,
+    ) -> Self {
+        Self {
+            timer,
+            duration,
+            idle: true,
+            expires_at: None,
+            armed: None,
+            on_expired,
+        }
+    }
+
+    /// Returns the time at which the deadline will expire, if it has
+    /// started.
+    pub const fn expires_at(&self) -> Option<Tm::Instant> {
+        self.expires_at
+    }
+
+    // starts the deadline if this is the first poll, and returns whether it
+    // has expired
+    pub(crate) fn is_expired(&mut self) -> bool {
+        let now = self.timer.now();
+        now >= *self.expires_at.get_or_insert(now + self.duration)
+    }
+
+    // `pending` should only be given if `is_expired` returned true at the
+    // start of this poll
+    pub(crate) fn check<Item>(
+        &mut self,
+        res: futures::Poll<Item, Error>,
+        pending: Option<Vec<usize>>,
+    ) -> futures::Poll<Item, Error> {
+        match res {
+            Ok(futures::Async::NotReady) => {
+                if let Some(pending) = pending {
+                    return Err((self.on_expired)(&Expired { pending }));
+                }
+                if let Some(deadline) = self.expires_at {
+                    if needs_arming(&mut self.armed) {
+                        self.timer.notify_at(deadline);
+                    }
+                }
+            }
+            Ok(futures::Async::Ready(_)) if self.idle => {
+                self.expires_at = None;
+                self.armed = None;
+            }
+            _ => {}
+        }
+        res
+    }
+}
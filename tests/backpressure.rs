@@ -0,0 +1,114 @@
+use component_future::diagnostics::Backpressure;
+use component_future::testing::Harness;
+use futures::sink::Sink as _;
+use futures::stream::Stream as _;
+
+use std::cell::RefCell;
+
+struct Forward {
+    input: futures::sync::mpsc::UnboundedReceiver<u32>,
+    output: futures::sync::mpsc::Sender<u32>,
+    pending: Option<u32>,
+}
+
+impl Forward {
+    #[allow(clippy::type_complexity)]
+    const POLL_FNS:
+        &'static [&'static dyn for<'a> Fn(
+            &'a mut Self,
+        )
+            -> component_future::Poll<
+            Option<u32>,
+            String,
+        >] = &[&Self::poll_input, &Self::poll_output];
+
+    fn poll_input(&mut self) -> component_future::Poll<Option<u32>, String> {
+        if self.pending.is_some() {
+            return Ok(component_future::Async::NothingToDo);
+        }
+
+        let input = component_future::try_ready!(self
+            .input
+            .poll()
+            .map_err(|()| "input failed".to_string()));
+        if let Some(input) = input {
+            self.pending = Some(input);
+            Ok(component_future::Async::DidWork)
+        } else {
+            Ok(component_future::Async::Ready(None))
+        }
+    }
+
+    fn poll_output(&mut self) -> component_future::Poll<Option<u32>, String> {
+        if let Some(val) = self.pending.take() {
+            match self.output.start_send(val).map_err(|e| e.to_string())? {
+                futures::AsyncSink::Ready => {
+                    Ok(component_future::Async::Ready(Some(val)))
+                }
+                futures::AsyncSink::NotReady(val) => {
+                    self.pending = Some(val);
+                    Ok(component_future::Async::Blocked)
+                }
+            }
+        } else {
+            Ok(component_future::Async::NothingToDo)
+        }
+    }
+}
+
+thread_local! {
+    static REPORTS: RefCell<Vec<Backpressure>> = const { RefCell::new(vec![]) };
+}
+
+fn record(backpressure: &Backpressure) {
+    REPORTS.with(|reports| reports.borrow_mut().push(backpressure.clone()));
+}
+
+impl futures::stream::Stream for Forward {
+    type Item = u32;
+    type Error = String;
+
+    fn poll(&mut self) -> futures::Poll<Option<Self::Item>, Self::Error> {
+        component_future::Driver::new()
+            .on_backpressure(record)
+            .poll_stream(self, Self::POLL_FNS)
+    }
+}
+
+#[test]
+fn test_backpressure() {
+    let (input_tx, input_rx) = futures::sync::mpsc::unbounded();
+    let (output_tx, output_rx) = futures::sync::mpsc::channel(0);
+    let mut output_rx = output_rx.wait();
+    let mut harness = Harness::new(Forward {
+        input: input_rx,
+        output: output_tx,
+        pending: None,
+    });
+
+    input_tx.unbounded_send(1).unwrap();
+    input_tx.unbounded_send(2).unwrap();
+    assert_eq!(harness.poll_stream(), Ok(futures::Async::Ready(Some(1))));
+    REPORTS.with(|reports| assert!(reports.borrow().is_empty()));
+
+    // the channel is full, so the second value can't be sent yet
+    assert_eq!(harness.poll_stream(), Ok(futures::Async::NotReady));
+    REPORTS.with(|reports| {
+        let reports = reports.borrow();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].blocked(), &[1]);
+        assert_eq!(
+            reports[0].to_string(),
+            "components [1] are blocked on full downstream sinks"
+        );
+    });
+
+    assert_eq!(output_rx.next(), Some(Ok(1)));
+    assert!(harness.is_notified());
+    assert_eq!(harness.poll_stream(), Ok(futures::Async::Ready(Some(2))));
+    assert_eq!(output_rx.next(), Some(Ok(2)));
+
+    // waiting for input isn't reported
+    assert_eq!(harness.poll_stream(), Ok(futures::Async::NotReady));
+    REPORTS.with(|reports| assert_eq!(reports.borrow().len(), 1));
+}
@@ -0,0 +1,97 @@
+use component_future::testing::{Harness, MockStream};
+use futures::future::Future as _;
+use futures::stream::Stream as _;
+
+struct Lines {
+    input: MockStream<u32, String>,
+    done: bool,
+}
+
+impl Lines {
+    #[allow(clippy::type_complexity)]
+    const POLL_FNS:
+        &'static [&'static dyn for<'a> Fn(
+            &'a mut Self,
+        )
+            -> component_future::Poll<
+            Option<u32>,
+            String,
+        >] = &[&Self::poll_input];
+
+    fn new(
+        steps: impl IntoIterator<Item = futures::Poll<Option<u32>, String>>,
+    ) -> Self {
+        Self {
+            input: MockStream::from_polls(steps),
+            done: false,
+        }
+    }
+
+    fn poll_input(&mut self) -> component_future::Poll<Option<u32>, String> {
+        if self.done {
+            return Ok(component_future::Async::Ready(None));
+        }
+
+        let line = component_future::try_ready!(self.input.poll());
+        self.done = line.is_none();
+        Ok(component_future::Async::Ready(line))
+    }
+}
+
+impl futures::stream::Stream for Lines {
+    type Item = Vec<u32>;
+    type Error = String;
+
+    fn poll(&mut self) -> futures::Poll<Option<Self::Item>, Self::Error> {
+        component_future::poll_stream_batch(self, 2, Self::POLL_FNS)
+    }
+}
+
+#[test]
+fn test_batch() {
+    let mut harness = Harness::new(Lines::new(vec![
+        Ok(futures::Async::Ready(Some(1))),
+        Ok(futures::Async::Ready(Some(2))),
+        Ok(futures::Async::Ready(Some(3))),
+        Ok(futures::Async::NotReady),
+        Ok(futures::Async::NotReady),
+        Ok(futures::Async::Ready(Some(4))),
+        Ok(futures::Async::Ready(None)),
+    ]));
+
+    assert_eq!(
+        harness.poll_stream(),
+        Ok(futures::Async::Ready(Some(vec![1, 2])))
+    );
+    assert_eq!(
+        harness.poll_stream(),
+        Ok(futures::Async::Ready(Some(vec![3])))
+    );
+    assert_eq!(harness.poll_stream(), Ok(futures::Async::NotReady));
+    assert_eq!(
+        harness.poll_stream(),
+        Ok(futures::Async::Ready(Some(vec![4])))
+    );
+    assert_eq!(harness.poll_stream(), Ok(futures::Async::Ready(None)));
+}
+
+#[test]
+fn test_batch_into_error() {
+    let mut lines = Lines::new(vec![
+        Ok(futures::Async::Ready(Some(1))),
+        Err("oops".to_string()),
+    ]);
+    let mut buf = vec![0];
+
+    let res = futures::future::poll_fn(|| {
+        component_future::poll_stream_batch_into(
+            &mut lines,
+            &mut buf,
+            10,
+            Lines::POLL_FNS,
+        )
+    })
+    .wait();
+    assert_eq!(res, Err("oops".to_string()));
+    assert_eq!(buf, vec![0, 1]);
+}
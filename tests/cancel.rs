@@ -0,0 +1,90 @@
+use component_future::cancel::{Cancellable, Token};
+use component_future::testing::Harness;
+use futures::future::Future as _;
+
+type Response = Box<dyn futures::future::Future<Item = u32, Error = String>>;
+
+struct Request {
+    response: Response,
+    cancel: Token,
+    fallback: Option<u32>,
+}
+
+impl Request {
+    #[allow(clippy::type_complexity)]
+    const POLL_FNS: &'static [&'static dyn component_future::Component<
+        Self,
+        u32,
+        String,
+    >] = &[
+        &Cancellable::new(|s: &mut Self| &mut s.cancel, &Self::cancelled),
+        &Self::poll_response,
+    ];
+
+    fn cancelled(&mut self) -> Result<u32, String> {
+        self.fallback.ok_or_else(|| "cancelled".to_string())
+    }
+
+    fn poll_response(&mut self) -> component_future::Poll<u32, String> {
+        let val = component_future::try_ready!(self.response.poll());
+        Ok(component_future::Async::Ready(val))
+    }
+}
+
+impl futures::future::Future for Request {
+    type Item = u32;
+    type Error = String;
+
+    fn poll(&mut self) -> futures::Poll<Self::Item, Self::Error> {
+        component_future::poll_future(self, Self::POLL_FNS)
+    }
+}
+
+// never ready, and never notifies the task
+fn pending() -> Response {
+    Box::new(futures::future::empty())
+}
+
+#[test]
+fn test_cancel_error() {
+    let cancel = Token::new();
+    let mut harness = Harness::new(Request {
+        response: pending(),
+        cancel: cancel.clone(),
+        fallback: None,
+    });
+
+    assert_eq!(harness.poll_future(), Ok(futures::Async::NotReady));
+    assert!(!harness.is_notified());
+
+    cancel.cancel();
+    assert!(cancel.is_cancelled());
+    assert!(harness.is_notified());
+    assert_eq!(harness.poll_future(), Err("cancelled".to_string()));
+}
+
+#[test]
+fn test_cancel_value() {
+    let cancel = Token::new();
+    let mut harness = Harness::new(Request {
+        response: pending(),
+        cancel: cancel.clone(),
+        fallback: Some(3),
+    });
+
+    assert_eq!(harness.poll_future(), Ok(futures::Async::NotReady));
+    cancel.clone().cancel();
+    assert!(harness.is_notified());
+    assert_eq!(harness.poll_future(), Ok(futures::Async::Ready(3)));
+}
+
+#[test]
+fn test_cancel_unused() {
+    let mut harness = Harness::new(Request {
+        response: Box::new(futures::future::ok(5)),
+        cancel: Token::new(),
+        fallback: None,
+    });
+
+    assert_eq!(harness.poll_future(), Ok(futures::Async::Ready(5)));
+}
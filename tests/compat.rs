@@ -0,0 +1,188 @@
+#![cfg(feature = "compat")]
+
+use component_future::compat::{Compat01, Compat03};
+use futures03::future::FutureExt as _;
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::Context;
+
+mod run;
+
+fn send_later<T: Send + 'static>(f: impl FnOnce(T) + Send + 'static, t: T) {
+    std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        f(t);
+    });
+}
+
+// a futures 0.3 future with one component which polls a futures 0.1 future
+struct Mixed03 {
+    old: futures::sync::oneshot::Receiver<u32>,
+    new: futures03::channel::oneshot::Receiver<u32>,
+    old_val: Option<u32>,
+}
+
+impl Mixed03 {
+    const POLL_FNS:
+        &'static [&'static dyn component_future::task::Component<
+            Self,
+            u32,
+            String,
+        >] = &[&Compat01(Self::poll_old), &Self::poll_new];
+
+    fn poll_old(&mut self) -> component_future::Poll<u32, String> {
+        if self.old_val.is_some() {
+            return Ok(component_future::Async::NothingToDo);
+        }
+
+        let val = component_future::try_ready_compat!(futures::Future::poll(
+            &mut self.old
+        )
+        .map_err(|e| e.to_string()));
+        self.old_val = Some(val);
+        Ok(component_future::Async::DidWork)
+    }
+
+    fn poll_new(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> component_future::Poll<u32, String> {
+        if let Some(old_val) = self.old_val {
+            let val = component_future::try_ready_compat!(self
+                .new
+                .poll_unpin(cx)
+                .map_err(|e| e.to_string()));
+            Ok(component_future::Async::Ready(old_val + val))
+        } else {
+            Ok(component_future::Async::NothingToDo)
+        }
+    }
+}
+
+impl Future for Mixed03 {
+    type Output = Result<u32, String>;
+
+    fn poll(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> std::task::Poll<Self::Output> {
+        component_future::task::poll_future(
+            self.get_mut(),
+            cx,
+            Self::POLL_FNS,
+        )
+    }
+}
+
+// a futures 0.1 future with one component which polls a futures 0.3 future
+struct Mixed01 {
+    old: futures::sync::oneshot::Receiver<u32>,
+    new: futures03::channel::oneshot::Receiver<u32>,
+    new_val: Option<u32>,
+}
+
+impl Mixed01 {
+    const POLL_FNS: &'static [&'static dyn component_future::Component<
+        Self,
+        u32,
+        String,
+    >] = &[&Compat03(Self::poll_new), &Self::poll_old];
+
+    fn poll_new(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> component_future::Poll<u32, String> {
+        if self.new_val.is_some() {
+            return Ok(component_future::Async::NothingToDo);
+        }
+
+        let val = component_future::try_ready_compat!(self
+            .new
+            .poll_unpin(cx)
+            .map_err(|e| e.to_string()));
+        self.new_val = Some(val);
+        Ok(component_future::Async::DidWork)
+    }
+
+    fn poll_old(&mut self) -> component_future::Poll<u32, String> {
+        if let Some(new_val) = self.new_val {
+            let val = component_future::try_ready_compat!(
+                futures::Future::poll(&mut self.old)
+                    .map_err(|e| e.to_string())
+            );
+            Ok(component_future::Async::Ready(new_val * val))
+        } else {
+            Ok(component_future::Async::NothingToDo)
+        }
+    }
+}
+
+impl futures::future::Future for Mixed01 {
+    type Item = u32;
+    type Error = String;
+
+    fn poll(&mut self) -> futures::Poll<Self::Item, Self::Error> {
+        component_future::poll_future(self, Self::POLL_FNS)
+    }
+}
+
+#[test]
+fn test_compat_01_in_03() {
+    let (old_tx, old_rx) = futures::sync::oneshot::channel();
+    let (new_tx, new_rx) = futures03::channel::oneshot::channel();
+    let fut = Mixed03 {
+        old: old_rx,
+        new: new_rx,
+        old_val: None,
+    };
+    send_later(|val| old_tx.send(val).unwrap(), 3);
+    send_later(|val| new_tx.send(val).unwrap(), 4);
+    assert_eq!(futures03::executor::block_on(fut), Ok(7));
+}
+
+#[test]
+fn test_compat_03_in_01() {
+    let (old_tx, old_rx) = futures::sync::oneshot::channel();
+    let (new_tx, new_rx) = futures03::channel::oneshot::channel();
+    let fut = Mixed01 {
+        old: old_rx,
+        new: new_rx,
+        new_val: None,
+    };
+    send_later(|val| new_tx.send(val).unwrap(), 3);
+    send_later(|val| old_tx.send(val).unwrap(), 4);
+    assert_eq!(run::future(fut), Ok(12));
+}
+
+fn blocked01(_: &mut ()) -> component_future::Poll<(), ()> {
+    Ok(component_future::Async::Blocked)
+}
+
+fn blocked03(
+    _: &mut (),
+    _: &mut Context<'_>,
+) -> component_future::Poll<(), ()> {
+    Ok(component_future::Async::Blocked)
+}
+
+#[test]
+fn test_compat_blocked() {
+    let res =
+        futures03::executor::block_on(futures03::future::poll_fn(|cx| {
+            std::task::Poll::Ready(component_future::task::Component::poll(
+                &Compat01(blocked01),
+                &mut (),
+                cx,
+            ))
+        }));
+    assert_eq!(res, Ok(component_future::Async::Blocked));
+
+    let res = futures::future::Future::wait(futures::future::lazy(|| {
+        Ok::<_, ()>(component_future::Component::poll(
+            &Compat03(blocked03),
+            &mut (),
+        ))
+    }));
+    assert_eq!(res, Ok(Ok(component_future::Async::Blocked)));
+}
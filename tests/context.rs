@@ -0,0 +1,163 @@
+use futures03::stream::StreamExt as _;
+
+use std::pin::Pin;
+
+mod run;
+
+struct Config {
+    multiplier: u32,
+    processed: usize,
+}
+
+struct State {
+    input:
+        Box<dyn futures::stream::Stream<Item = u32, Error = String> + Send>,
+    pending: Option<u32>,
+}
+
+impl State {
+    #[allow(clippy::type_complexity)]
+    const POLL_FNS:
+        &'static [&'static dyn for<'a, 'b> Fn(
+            &'a mut Self,
+            &'b mut Config,
+        )
+            -> component_future::Poll<
+            Option<u32>,
+            String,
+        >] = &[&Self::poll_input, &Self::poll_output];
+
+    fn poll_input(
+        &mut self,
+        _config: &mut Config,
+    ) -> component_future::Poll<Option<u32>, String> {
+        if self.pending.is_some() {
+            return Ok(component_future::Async::NothingToDo);
+        }
+
+        if let Some(val) = component_future::try_ready!(self.input.poll()) {
+            self.pending = Some(val);
+            Ok(component_future::Async::DidWork)
+        } else {
+            Ok(component_future::Async::Ready(None))
+        }
+    }
+
+    fn poll_output(
+        &mut self,
+        config: &mut Config,
+    ) -> component_future::Poll<Option<u32>, String> {
+        if let Some(val) = self.pending.take() {
+            config.processed += 1;
+            Ok(component_future::Async::Ready(Some(
+                val * config.multiplier,
+            )))
+        } else {
+            Ok(component_future::Async::NothingToDo)
+        }
+    }
+}
+
+struct Scaled {
+    state: State,
+    config: Config,
+}
+
+impl futures::stream::Stream for Scaled {
+    type Item = u32;
+    type Error = String;
+
+    fn poll(&mut self) -> futures::Poll<Option<Self::Item>, Self::Error> {
+        component_future::poll_stream_with(
+            &mut self.state,
+            &mut self.config,
+            State::POLL_FNS,
+        )
+    }
+}
+
+#[test]
+fn test_context() {
+    let stream = Scaled {
+        state: State {
+            input: Box::new(futures::stream::iter_ok(vec![1, 2, 3])),
+            pending: None,
+        },
+        config: Config {
+            multiplier: 10,
+            processed: 0,
+        },
+    };
+    assert_eq!(run::stream(stream), Ok(vec![10, 20, 30]));
+}
+
+struct TaskState {
+    input: futures03::stream::Iter<std::vec::IntoIter<u32>>,
+}
+
+impl TaskState {
+    #[allow(clippy::type_complexity)]
+    const POLL_FNS: &'static [&'static dyn for<'a, 'b, 'c, 'd> Fn(
+        &'a mut Self,
+        &'b mut component_future::task::WithContext<'c, 'd, Config>,
+    ) -> component_future::Poll<
+        Option<u32>,
+        String,
+    >] = &[&Self::poll_input];
+
+    fn poll_input(
+        &mut self,
+        ctx: &mut component_future::task::WithContext<'_, '_, Config>,
+    ) -> component_future::Poll<Option<u32>, String> {
+        let val = match self.input.poll_next_unpin(ctx.task()) {
+            std::task::Poll::Ready(val) => val,
+            std::task::Poll::Pending => {
+                return Ok(component_future::Async::NotReady)
+            }
+        };
+        ctx.processed += 1;
+        Ok(component_future::Async::Ready(
+            val.map(|i| i * ctx.multiplier),
+        ))
+    }
+}
+
+struct TaskScaled {
+    state: TaskState,
+    config: Config,
+}
+
+impl futures03::stream::Stream for TaskScaled {
+    type Item = Result<u32, String>;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        component_future::task::poll_stream_with(
+            &mut this.state,
+            &mut this.config,
+            cx,
+            TaskState::POLL_FNS,
+        )
+    }
+}
+
+#[test]
+fn test_task_context() {
+    let mut stream = TaskScaled {
+        state: TaskState {
+            input: futures03::stream::iter(vec![1, 2, 3]),
+        },
+        config: Config {
+            multiplier: 10,
+            processed: 0,
+        },
+    };
+    assert_eq!(
+        futures03::executor::block_on((&mut stream).collect::<Vec<_>>()),
+        vec![Ok(10), Ok(20), Ok(30)]
+    );
+    assert_eq!(stream.config.processed, 4);
+}
@@ -0,0 +1,292 @@
+use component_future::diagnostics::Expired;
+use component_future::testing::{Harness, MockStream, VirtualClock};
+use component_future::timeout::{Deadline, ThreadTimer};
+use futures::future::Future as _;
+use futures::stream::Stream as _;
+
+use std::time::Duration;
+
+type Response = Box<dyn futures::future::Future<Item = u32, Error = String>>;
+
+fn expired(expired: &Expired) -> String {
+    expired.to_string()
+}
+
+struct Both {
+    first: Response,
+    first_val: Option<u32>,
+    second: Response,
+    deadline: Deadline<VirtualClock, String>,
+}
+
+impl Both {
+    #[allow(clippy::type_complexity)]
+    const POLL_FNS:
+        &'static [&'static dyn for<'a> Fn(
+            &'a mut Self,
+        )
+            -> component_future::Poll<
+            u32,
+            String,
+        >] = &[&Self::poll_first, &Self::poll_second];
+
+    fn poll_first(&mut self) -> component_future::Poll<u32, String> {
+        if self.first_val.is_some() {
+            return Ok(component_future::Async::NothingToDo);
+        }
+
+        let val = component_future::try_ready!(self.first.poll());
+        self.first_val = Some(val);
+        Ok(component_future::Async::DidWork)
+    }
+
+    fn poll_second(&mut self) -> component_future::Poll<u32, String> {
+        let val = component_future::try_ready!(self.second.poll());
+        if let Some(first_val) = self.first_val {
+            Ok(component_future::Async::Ready(first_val + val))
+        } else {
+            Ok(component_future::Async::NothingToDo)
+        }
+    }
+}
+
+impl futures::future::Future for Both {
+    type Item = u32;
+    type Error = String;
+
+    fn poll(&mut self) -> futures::Poll<Self::Item, Self::Error> {
+        component_future::poll_future_until(
+            self,
+            |s| &mut s.deadline,
+            Self::POLL_FNS,
+        )
+    }
+}
+
+#[test]
+fn test_deadline_future() {
+    let clock = VirtualClock::new();
+    let mut harness = Harness::new(Both {
+        first: Box::new(futures::future::ok(1)),
+        first_val: None,
+        second: Box::new(futures::future::empty()),
+        deadline: Deadline::new(
+            clock.clone(),
+            Duration::from_secs(10),
+            expired,
+        ),
+    });
+
+    assert_eq!(harness.poll_future(), Ok(futures::Async::NotReady));
+    clock.advance(Duration::from_secs(9));
+    assert!(!harness.is_notified());
+    clock.advance(Duration::from_secs(1));
+    assert!(harness.is_notified());
+    assert_eq!(
+        harness.poll_future(),
+        Err("deadline expired while components [1] were still pending"
+            .to_string())
+    );
+}
+
+struct Events {
+    input: MockStream<u32, String>,
+    deadline: Deadline<VirtualClock, String>,
+}
+
+impl Events {
+    #[allow(clippy::type_complexity)]
+    const POLL_FNS:
+        &'static [&'static dyn for<'a> Fn(
+            &'a mut Self,
+        )
+            -> component_future::Poll<
+            Option<u32>,
+            String,
+        >] = &[&Self::poll_input];
+
+    fn poll_input(&mut self) -> component_future::Poll<Option<u32>, String> {
+        let val = component_future::try_ready!(self.input.poll());
+        Ok(component_future::Async::Ready(val))
+    }
+}
+
+impl futures::stream::Stream for Events {
+    type Item = u32;
+    type Error = String;
+
+    fn poll(&mut self) -> futures::Poll<Option<Self::Item>, Self::Error> {
+        component_future::poll_stream_until(
+            self,
+            |s| &mut s.deadline,
+            Self::POLL_FNS,
+        )
+    }
+}
+
+#[test]
+fn test_deadline_idle_stream() {
+    let clock = VirtualClock::new();
+    let mut harness = Harness::new(Events {
+        input: MockStream::new(vec![
+            futures::Async::Ready(Some(1)),
+            futures::Async::NotReady,
+            futures::Async::Ready(Some(2)),
+            futures::Async::NotReady,
+            futures::Async::NotReady,
+        ]),
+        deadline: Deadline::idle(
+            clock.clone(),
+            Duration::from_secs(5),
+            expired,
+        ),
+    });
+
+    assert_eq!(harness.poll_stream(), Ok(futures::Async::Ready(Some(1))));
+    clock.advance(Duration::from_secs(3));
+    assert_eq!(harness.poll_stream(), Ok(futures::Async::NotReady));
+    clock.advance(Duration::from_secs(3));
+    // more than 5 seconds since the stream started, but not since the last
+    // item
+    assert_eq!(harness.poll_stream(), Ok(futures::Async::Ready(Some(2))));
+    assert_eq!(harness.poll_stream(), Ok(futures::Async::NotReady));
+    clock.advance(Duration::from_secs(5));
+    assert_eq!(
+        harness.poll_stream(),
+        Err("deadline expired while components [0] were still pending"
+            .to_string())
+    );
+}
+
+// counts the number of times a wakeup was requested
+#[derive(Clone)]
+struct CountingTimer {
+    clock: VirtualClock,
+    calls: std::rc::Rc<std::cell::Cell<usize>>,
+}
+
+impl component_future::timeout::Timer for CountingTimer {
+    type Instant = Duration;
+
+    fn now(&self) -> Self::Instant {
+        self.clock.now()
+    }
+
+    fn notify_at(&self, deadline: Self::Instant) {
+        self.calls.set(self.calls.get() + 1);
+        self.clock.notify_at(deadline);
+    }
+}
+
+struct Counted {
+    input: MockStream<u32, String>,
+    deadline: Deadline<CountingTimer, String>,
+}
+
+impl futures::stream::Stream for Counted {
+    type Item = u32;
+    type Error = String;
+
+    fn poll(&mut self) -> futures::Poll<Option<Self::Item>, Self::Error> {
+        component_future::poll_stream_until(
+            self,
+            |s| &mut s.deadline,
+            &[|s: &mut Self| {
+                let val = component_future::try_ready!(s.input.poll());
+                Ok(component_future::Async::Ready(val))
+            }],
+        )
+    }
+}
+
+#[test]
+fn test_deadline_arms_once() {
+    let timer = CountingTimer {
+        clock: VirtualClock::new(),
+        calls: std::rc::Rc::default(),
+    };
+    let mut harness = Harness::new(Counted {
+        input: MockStream::new(vec![
+            futures::Async::NotReady,
+            futures::Async::NotReady,
+            futures::Async::NotReady,
+            futures::Async::Ready(Some(1)),
+            futures::Async::NotReady,
+            futures::Async::NotReady,
+        ]),
+        deadline: Deadline::idle(
+            timer.clone(),
+            Duration::from_secs(5),
+            expired,
+        ),
+    });
+
+    for _ in 0..3 {
+        assert_eq!(harness.poll_stream(), Ok(futures::Async::NotReady));
+    }
+    assert_eq!(timer.calls.get(), 1);
+
+    // producing an item restarts the deadline, which has to be armed again
+    assert_eq!(harness.poll_stream(), Ok(futures::Async::Ready(Some(1))));
+    for _ in 0..2 {
+        assert_eq!(harness.poll_stream(), Ok(futures::Async::NotReady));
+    }
+    assert_eq!(timer.calls.get(), 2);
+}
+
+struct Ticks {
+    input: futures::sync::mpsc::UnboundedReceiver<u32>,
+    deadline: Deadline<ThreadTimer, String>,
+}
+
+impl futures::stream::Stream for Ticks {
+    type Item = u32;
+    type Error = String;
+
+    fn poll(&mut self) -> futures::Poll<Option<Self::Item>, Self::Error> {
+        component_future::poll_stream_until(
+            self,
+            |s| &mut s.deadline,
+            &[|s: &mut Self| {
+                let val = component_future::try_ready!(s
+                    .input
+                    .poll()
+                    .map_err(|()| "closed".to_string()));
+                Ok(component_future::Async::Ready(val))
+            }],
+        )
+    }
+}
+
+#[test]
+fn test_deadline_idle_thread_timer() {
+    let (sender, input) = futures::sync::mpsc::unbounded();
+    let mut harness = Harness::new(Ticks {
+        input,
+        deadline: Deadline::idle(
+            ThreadTimer::new(),
+            Duration::from_millis(200),
+            expired,
+        ),
+    });
+
+    assert_eq!(harness.poll_stream(), Ok(futures::Async::NotReady));
+    std::thread::sleep(Duration::from_millis(100));
+
+    // restarts the deadline, so the new one is after the first one
+    sender.unbounded_send(1).unwrap();
+    assert_eq!(harness.poll_stream(), Ok(futures::Async::Ready(Some(1))));
+    assert_eq!(harness.poll_stream(), Ok(futures::Async::NotReady));
+
+    // past the first deadline, but not the second
+    std::thread::sleep(Duration::from_millis(150));
+    assert_eq!(harness.poll_stream(), Ok(futures::Async::NotReady));
+
+    std::thread::sleep(Duration::from_millis(300));
+    assert!(harness.is_notified());
+    assert_eq!(
+        harness.poll_stream(),
+        Err("deadline expired while components [0] were still pending"
+            .to_string())
+    );
+}
@@ -0,0 +1,114 @@
+use component_future::forward::Forward;
+use component_future::project::Project;
+use component_future::testing::Harness;
+use futures::future::Future as _;
+use futures::stream::Stream as _;
+
+type Input = Box<dyn futures::stream::Stream<Item = u32, Error = Error>>;
+type Output = futures::sync::mpsc::Sender<u32>;
+
+#[derive(Debug, PartialEq, Eq)]
+enum Error {
+    Recv(String),
+    Send(u32),
+}
+
+impl From<futures::sync::mpsc::SendError<u32>> for Error {
+    fn from(e: futures::sync::mpsc::SendError<u32>) -> Self {
+        Self::Send(e.into_inner())
+    }
+}
+
+struct Relay {
+    forward: Forward<Input, Output>,
+}
+
+impl Relay {
+    fn new(input: Input, output: Output) -> Self {
+        Self {
+            forward: Forward::new(input, output),
+        }
+    }
+
+    const POLL_FNS: &'static [&'static dyn component_future::Component<
+        Self,
+        (),
+        Error,
+    >] = &[
+        &Project::new(|relay: &mut Self| &mut relay.forward, &Forward::poll),
+        &Self::poll_done,
+    ];
+
+    fn poll_done(&mut self) -> component_future::Poll<(), Error> {
+        if self.forward.is_done() {
+            Ok(component_future::Async::Ready(()))
+        } else {
+            Ok(component_future::Async::NothingToDo)
+        }
+    }
+}
+
+impl futures::future::Future for Relay {
+    type Item = ();
+    type Error = Error;
+
+    fn poll(&mut self) -> futures::Poll<Self::Item, Self::Error> {
+        component_future::poll_future(self, Self::POLL_FNS)
+    }
+}
+
+#[test]
+fn test_forward() {
+    let (input_tx, input_rx) = futures::sync::mpsc::unbounded();
+    let (output_tx, output_rx) = futures::sync::mpsc::channel(0);
+    let mut output_rx = output_rx.wait();
+    let mut harness = Harness::new(Relay::new(
+        Box::new(input_rx.map_err(|()| Error::Recv("input".to_string()))),
+        output_tx,
+    ));
+
+    assert_eq!(harness.poll_future(), Ok(futures::Async::NotReady));
+
+    input_tx.unbounded_send(1).unwrap();
+    input_tx.unbounded_send(2).unwrap();
+    input_tx.unbounded_send(3).unwrap();
+    drop(input_tx);
+    assert!(harness.is_notified());
+
+    // the channel only has room for one item at a time
+    assert_eq!(harness.poll_future(), Ok(futures::Async::NotReady));
+    assert!(!harness.get_ref().forward.is_done());
+    let mut received = vec![];
+    loop {
+        received.push(output_rx.next().unwrap().unwrap());
+        assert!(harness.is_notified());
+        if harness.poll_future() == Ok(futures::Async::Ready(())) {
+            break;
+        }
+    }
+
+    // the input has ended, so the output has been closed
+    assert!(harness.get_ref().forward.is_done());
+    drop(harness);
+    received.extend(output_rx.map(Result::unwrap));
+    assert_eq!(received, vec![1, 2, 3]);
+}
+
+#[test]
+fn test_forward_error() {
+    let (output_tx, _output_rx) = futures::sync::mpsc::channel(0);
+    let relay = Relay::new(
+        Box::new(futures::stream::iter_result(vec![
+            Ok(1),
+            Err(Error::Recv("input".to_string())),
+        ])),
+        output_tx,
+    );
+    assert_eq!(relay.wait(), Err(Error::Recv("input".to_string())));
+
+    let (output_tx, output_rx) = futures::sync::mpsc::channel(0);
+    drop(output_rx);
+    let relay =
+        Relay::new(Box::new(futures::stream::iter_ok(vec![1, 2])), output_tx);
+    assert_eq!(relay.wait(), Err(Error::Send(1)));
+}
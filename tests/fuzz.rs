@@ -0,0 +1,123 @@
+use component_future::testing::{fuzz_stream, Fuzz, MockStream};
+use futures::stream::Stream as _;
+
+struct Pairs {
+    left: MockStream<u32, String>,
+    right: MockStream<u32, String>,
+    pending_left: Option<u32>,
+    pending_right: Option<u32>,
+    left_done: bool,
+    right_done: bool,
+}
+
+impl Pairs {
+    fn new(fuzz: &mut Fuzz) -> Self {
+        Self {
+            left: fuzz.mock_stream(vec![
+                futures::Async::Ready(Some(1)),
+                futures::Async::Ready(Some(2)),
+                futures::Async::Ready(Some(3)),
+                futures::Async::Ready(None),
+            ]),
+            right: fuzz.mock_stream(vec![
+                futures::Async::Ready(Some(10)),
+                futures::Async::Ready(Some(20)),
+                futures::Async::Ready(Some(30)),
+                futures::Async::Ready(None),
+            ]),
+            pending_left: None,
+            pending_right: None,
+            left_done: false,
+            right_done: false,
+        }
+    }
+
+    #[allow(clippy::type_complexity)]
+    const POLL_FNS:
+        &'static [&'static dyn for<'a> Fn(
+            &'a mut Self,
+        )
+            -> component_future::Poll<
+            Option<u32>,
+            String,
+        >] = &[&Self::poll_left, &Self::poll_right, &Self::poll_pair];
+
+    // buggy: keeps overwriting the pending value instead of waiting for it
+    // to be consumed
+    #[allow(clippy::type_complexity)]
+    const BUGGY_POLL_FNS:
+        &'static [&'static dyn for<'a> Fn(
+            &'a mut Self,
+        )
+            -> component_future::Poll<
+            Option<u32>,
+            String,
+        >] = &[&Self::poll_left_buggy, &Self::poll_right, &Self::poll_pair];
+
+    fn poll_left(&mut self) -> component_future::Poll<Option<u32>, String> {
+        if self.pending_left.is_some() || self.left_done {
+            return Ok(component_future::Async::NothingToDo);
+        }
+        match component_future::try_ready!(self.left.poll()) {
+            Some(val) => self.pending_left = Some(val),
+            None => self.left_done = true,
+        }
+        Ok(component_future::Async::DidWork)
+    }
+
+    fn poll_left_buggy(
+        &mut self,
+    ) -> component_future::Poll<Option<u32>, String> {
+        if self.left_done {
+            return Ok(component_future::Async::NothingToDo);
+        }
+        match component_future::try_ready!(self.left.poll()) {
+            Some(val) => self.pending_left = Some(val),
+            None => self.left_done = true,
+        }
+        Ok(component_future::Async::DidWork)
+    }
+
+    fn poll_right(&mut self) -> component_future::Poll<Option<u32>, String> {
+        if self.pending_right.is_some() || self.right_done {
+            return Ok(component_future::Async::NothingToDo);
+        }
+        match component_future::try_ready!(self.right.poll()) {
+            Some(val) => self.pending_right = Some(val),
+            None => self.right_done = true,
+        }
+        Ok(component_future::Async::DidWork)
+    }
+
+    fn poll_pair(&mut self) -> component_future::Poll<Option<u32>, String> {
+        if self.left_done && self.right_done {
+            return Ok(component_future::Async::Ready(None));
+        }
+        if self.pending_left.is_none() || self.pending_right.is_none() {
+            return Ok(component_future::Async::NothingToDo);
+        }
+        let left = self.pending_left.take().unwrap();
+        let right = self.pending_right.take().unwrap();
+        Ok(component_future::Async::Ready(Some(left + right)))
+    }
+}
+
+impl futures::stream::Stream for Pairs {
+    type Item = u32;
+    type Error = String;
+
+    fn poll(&mut self) -> futures::Poll<Option<Self::Item>, Self::Error> {
+        component_future::poll_stream(self, Self::POLL_FNS)
+    }
+}
+
+#[test]
+fn test_order_independent() {
+    fuzz_stream(100, Pairs::new, Pairs::POLL_FNS);
+}
+
+#[test]
+#[should_panic(expected = "seed 3 produced")]
+fn test_order_dependent() {
+    fuzz_stream(100, Pairs::new, Pairs::BUGGY_POLL_FNS);
+}
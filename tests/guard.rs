@@ -0,0 +1,137 @@
+use component_future::testing::{Harness, MockFuture};
+use futures::future::Future as _;
+
+mod run;
+
+type Boxed =
+    Box<dyn futures::future::Future<Item = u32, Error = String> + Send>;
+
+struct TwoFutures {
+    fut1: Option<Boxed>,
+    fut2: Option<Boxed>,
+    val: u32,
+}
+
+impl TwoFutures {
+    fn new<F1, F2>(fut1: F1, fut2: F2) -> Self
+    where
+        F1: futures::future::Future<Item = u32, Error = String>
+            + Send
+            + 'static,
+        F2: futures::future::Future<Item = u32, Error = String>
+            + Send
+            + 'static,
+    {
+        Self {
+            fut1: Some(Box::new(fut1)),
+            fut2: Some(Box::new(fut2)),
+            val: 1,
+        }
+    }
+
+    #[allow(clippy::type_complexity)]
+    const POLL_FNS: &'static [(
+        fn(&Self) -> bool,
+        &'static dyn component_future::Component<Self, u32, String>,
+    )] = &[
+        (|s| s.fut1.is_some(), &Self::poll_future_1),
+        (
+            |s| s.fut1.is_none() && s.fut2.is_some(),
+            &Self::poll_future_2,
+        ),
+        (|s| s.fut1.is_none() && s.fut2.is_none(), &Self::poll_return),
+    ];
+
+    fn poll_future_1(&mut self) -> component_future::Poll<u32, String> {
+        let val =
+            component_future::try_ready!(self.fut1.as_mut().unwrap().poll());
+        self.val += val;
+        self.fut1.take();
+        Ok(component_future::Async::DidWork)
+    }
+
+    fn poll_future_2(&mut self) -> component_future::Poll<u32, String> {
+        let val =
+            component_future::try_ready!(self.fut2.as_mut().unwrap().poll());
+        self.val *= val;
+        self.fut2.take();
+        Ok(component_future::Async::DidWork)
+    }
+
+    fn poll_return(&mut self) -> component_future::Poll<u32, String> {
+        Ok(component_future::Async::Ready(self.val))
+    }
+}
+
+impl futures::future::Future for TwoFutures {
+    type Item = u32;
+    type Error = String;
+
+    fn poll(&mut self) -> futures::Poll<Self::Item, Self::Error> {
+        component_future::poll_future_guarded(self, Self::POLL_FNS)
+    }
+}
+
+#[test]
+fn test_guarded_sequence() {
+    let cfut =
+        TwoFutures::new(futures::future::ok(3), futures::future::ok(5));
+    assert_eq!(run::future(cfut), Ok(20));
+}
+
+#[test]
+fn test_guarded_not_polled() {
+    let fut2 = MockFuture::new(vec![futures::Async::Ready(5)]);
+    let polls = fut2.polls();
+    let mut harness = Harness::new(TwoFutures::new(
+        MockFuture::new(vec![
+            futures::Async::NotReady,
+            futures::Async::Ready(3),
+        ]),
+        fut2,
+    ));
+
+    // the second future isn't polled until the first has finished
+    assert_eq!(harness.poll_future(), Ok(futures::Async::NotReady));
+    assert_eq!(polls.get(), 0);
+    assert_eq!(harness.poll_future(), Ok(futures::Async::Ready(20)));
+    assert_eq!(polls.get(), 1);
+}
+
+struct Stuck {
+    done: bool,
+}
+
+impl Stuck {
+    #[allow(clippy::type_complexity)]
+    const POLL_FNS: &'static [(
+        fn(&Self) -> bool,
+        &'static dyn component_future::Component<Self, (), String>,
+    )] = &[(|s| s.done, &Self::poll_done), (|_| true, &Self::poll_idle)];
+
+    fn poll_done(&mut self) -> component_future::Poll<(), String> {
+        Ok(component_future::Async::Ready(()))
+    }
+
+    fn poll_idle(&mut self) -> component_future::Poll<(), String> {
+        Ok(component_future::Async::NothingToDo)
+    }
+}
+
+impl futures::future::Future for Stuck {
+    type Item = ();
+    type Error = String;
+
+    fn poll(&mut self) -> futures::Poll<Self::Item, Self::Error> {
+        component_future::poll_future_guarded(self, Self::POLL_FNS)
+    }
+}
+
+#[test]
+#[should_panic(
+    expected = "all components returned NothingToDo (components [0] were \
+                disabled by their guards)"
+)]
+fn test_guarded_idle() {
+    let _ = Harness::new(Stuck { done: false }).poll_future();
+}
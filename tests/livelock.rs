@@ -0,0 +1,87 @@
+use component_future::diagnostics::Stall;
+use component_future::testing::{Harness, MockFuture};
+use futures::future::Future as _;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+struct Spinning {
+    fut: MockFuture<u32, String>,
+}
+
+impl Spinning {
+    #[allow(clippy::type_complexity)]
+    const POLL_FNS:
+        &'static [&'static dyn for<'a> Fn(
+            &'a mut Self,
+        )
+            -> component_future::Poll<
+            u32,
+            String,
+        >] = &[&Self::poll_fut, &Self::poll_spin];
+
+    fn poll_fut(&mut self) -> component_future::Poll<u32, String> {
+        let val = component_future::try_ready!(self.fut.poll());
+        Ok(component_future::Async::Ready(val))
+    }
+
+    fn poll_spin(&mut self) -> component_future::Poll<u32, String> {
+        Ok(component_future::Async::DidWork)
+    }
+}
+
+static STALLS: AtomicUsize = AtomicUsize::new(0);
+
+fn count_stall(stall: &Stall) {
+    assert_eq!(
+        stall,
+        &Stall::Livelock {
+            component: 1,
+            iterations: 11
+        }
+    );
+    STALLS.fetch_add(1, Ordering::SeqCst);
+}
+
+struct Reported(Spinning);
+
+impl futures::future::Future for Reported {
+    type Item = u32;
+    type Error = String;
+
+    fn poll(&mut self) -> futures::Poll<Self::Item, Self::Error> {
+        component_future::Driver::new()
+            .livelock_limit(10)
+            .on_stall(count_stall)
+            .poll_future(&mut self.0, Spinning::POLL_FNS)
+    }
+}
+
+impl futures::future::Future for Spinning {
+    type Item = u32;
+    type Error = String;
+
+    fn poll(&mut self) -> futures::Poll<Self::Item, Self::Error> {
+        component_future::Driver::new()
+            .livelock_limit(10)
+            .poll_future(self, Self::POLL_FNS)
+    }
+}
+
+#[test]
+#[should_panic(expected = "component 1 returned DidWork for 11 consecutive")]
+fn test_livelock_panics() {
+    let mut harness = Harness::new(Spinning {
+        fut: MockFuture::new(vec![futures::Async::NotReady; 100]),
+    });
+    let _ = harness.poll_future();
+}
+
+#[test]
+fn test_livelock_reported() {
+    let mut harness = Harness::new(Reported(Spinning {
+        fut: MockFuture::new(vec![futures::Async::NotReady; 100]),
+    }));
+    assert_eq!(harness.poll_future(), Ok(futures::Async::NotReady));
+    assert!(harness.is_notified());
+    assert_eq!(STALLS.load(Ordering::SeqCst), 1);
+}
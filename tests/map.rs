@@ -0,0 +1,146 @@
+use component_future::map::Map;
+use component_future::testing::MockStream;
+use futures::future::Future as _;
+use futures::stream::Stream as _;
+
+mod run;
+
+#[derive(Debug, PartialEq, Eq)]
+struct Tick(u32);
+
+#[derive(Debug, PartialEq, Eq)]
+struct TimerError;
+
+#[derive(Debug, PartialEq, Eq)]
+enum Event {
+    Request(String),
+    Tick(u32),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Error {
+    Request(String),
+    Timer,
+}
+
+impl From<TimerError> for Error {
+    fn from(_: TimerError) -> Self {
+        Self::Timer
+    }
+}
+
+struct Server {
+    requests: Option<MockStream<String, String>>,
+    timer: Option<MockStream<Tick, TimerError>>,
+}
+
+impl Server {
+    const POLL_FNS: &'static [&'static dyn component_future::Component<
+        Self,
+        Option<Event>,
+        Error,
+    >] = &[
+        &Map::new(
+            &Self::poll_requests,
+            |req: Option<String>| req.map(Event::Request),
+            Error::Request,
+        ),
+        &Map::new(
+            &Self::poll_timer,
+            |tick: Option<Tick>| tick.map(|Tick(n)| Event::Tick(n)),
+            Error::from,
+        ),
+        &Self::poll_end,
+    ];
+
+    fn poll_requests(
+        &mut self,
+    ) -> component_future::Poll<Option<String>, String> {
+        if let Some(requests) = &mut self.requests {
+            let req = component_future::try_ready!(requests.poll());
+            if req.is_some() {
+                Ok(component_future::Async::Ready(req))
+            } else {
+                self.requests.take();
+                Ok(component_future::Async::DidWork)
+            }
+        } else {
+            Ok(component_future::Async::NothingToDo)
+        }
+    }
+
+    fn poll_timer(
+        &mut self,
+    ) -> component_future::Poll<Option<Tick>, TimerError> {
+        if let Some(timer) = &mut self.timer {
+            let tick = component_future::try_ready!(timer.poll());
+            if tick.is_some() {
+                Ok(component_future::Async::Ready(tick))
+            } else {
+                self.timer.take();
+                Ok(component_future::Async::DidWork)
+            }
+        } else {
+            Ok(component_future::Async::NothingToDo)
+        }
+    }
+
+    fn poll_end(&mut self) -> component_future::Poll<Option<Event>, Error> {
+        if self.requests.is_none() && self.timer.is_none() {
+            Ok(component_future::Async::Ready(None))
+        } else {
+            Ok(component_future::Async::NothingToDo)
+        }
+    }
+}
+
+impl futures::stream::Stream for Server {
+    type Item = Event;
+    type Error = Error;
+
+    fn poll(&mut self) -> futures::Poll<Option<Self::Item>, Self::Error> {
+        component_future::poll_stream(self, Self::POLL_FNS)
+    }
+}
+
+#[test]
+fn test_map() {
+    let server = Server {
+        requests: Some(MockStream::new(vec![
+            futures::Async::Ready(Some("GET /".to_string())),
+            futures::Async::NotReady,
+            futures::Async::Ready(None),
+        ])),
+        timer: Some(MockStream::new(vec![
+            futures::Async::Ready(Some(Tick(1))),
+            futures::Async::Ready(Some(Tick(2))),
+            futures::Async::Ready(None),
+        ])),
+    };
+    assert_eq!(
+        run::stream(server),
+        Ok(vec![
+            Event::Request("GET /".to_string()),
+            Event::Tick(1),
+            Event::Tick(2),
+        ])
+    );
+}
+
+#[test]
+fn test_map_err() {
+    let server = Server {
+        requests: Some(MockStream::new(vec![futures::Async::NotReady])),
+        timer: Some(MockStream::from_polls(vec![Err(TimerError)])),
+    };
+    assert_eq!(server.collect().wait(), Err(Error::Timer));
+
+    let server = Server {
+        requests: Some(MockStream::from_polls(vec![Err("oops".to_string())])),
+        timer: None,
+    };
+    assert_eq!(
+        server.collect().wait(),
+        Err(Error::Request("oops".to_string()))
+    );
+}
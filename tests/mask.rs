@@ -0,0 +1,98 @@
+use component_future::mask::Mask;
+use component_future::testing::{Harness, MockStream};
+use futures::stream::Stream as _;
+
+struct Node {
+    mask: Mask,
+    leader: bool,
+    events: MockStream<u32, String>,
+    replicated: Vec<u32>,
+}
+
+impl Node {
+    #[allow(clippy::type_complexity)]
+    const POLL_FNS: &'static [&'static dyn component_future::Component<
+        Self,
+        Option<u32>,
+        String,
+    >] = &[&Self::poll_election, &Self::poll_replication];
+
+    fn new(events: MockStream<u32, String>) -> Self {
+        let mut mask = Mask::named(&["election", "replication"]);
+        mask.set_named("replication", false);
+        Self {
+            mask,
+            leader: false,
+            events,
+            replicated: vec![],
+        }
+    }
+
+    // doesn't report doing any work when it becomes the leader, so that the
+    // driver has to notice the change to the mask by itself
+    fn poll_election(
+        &mut self,
+    ) -> component_future::Poll<Option<u32>, String> {
+        self.mask.set_named("replication", self.leader);
+        Ok(component_future::Async::NothingToDo)
+    }
+
+    fn poll_replication(
+        &mut self,
+    ) -> component_future::Poll<Option<u32>, String> {
+        let event = component_future::try_ready!(self.events.poll());
+        if let Some(event) = event {
+            self.replicated.push(event);
+        }
+        Ok(component_future::Async::Ready(event))
+    }
+}
+
+impl futures::stream::Stream for Node {
+    type Item = u32;
+    type Error = String;
+
+    fn poll(&mut self) -> futures::Poll<Option<Self::Item>, Self::Error> {
+        component_future::poll_stream_masked(
+            self,
+            |node| &mut node.mask,
+            Self::POLL_FNS,
+        )
+    }
+}
+
+#[test]
+fn test_mask_toggle() {
+    let events = MockStream::new(vec![
+        futures::Async::Ready(Some(1)),
+        futures::Async::Ready(Some(2)),
+        futures::Async::Ready(None),
+    ]);
+    let polls = events.polls();
+    let mut harness = Harness::new(Node::new(events));
+
+    // becoming the leader during the pass enables replication on the next
+    // one
+    harness.get_mut().leader = true;
+    assert_eq!(harness.poll_stream(), Ok(futures::Async::Ready(Some(1))));
+    assert!(harness.get_ref().mask.is_enabled_named("replication"));
+    assert_eq!(polls.get(), 1);
+
+    assert_eq!(harness.poll_stream(), Ok(futures::Async::Ready(Some(2))));
+    assert_eq!(harness.poll_stream(), Ok(futures::Async::Ready(None)));
+    assert_eq!(harness.get_ref().replicated, vec![1, 2]);
+}
+
+#[test]
+#[should_panic(expected = "no component named \"follower\"")]
+fn test_mask_unknown_name() {
+    Mask::named(&["election", "replication"]).set_named("follower", true);
+}
+
+#[test]
+#[should_panic(expected = "the mask must have one entry per component")]
+fn test_mask_wrong_len() {
+    let mut harness = Harness::new(Node::new(MockStream::new(vec![])));
+    harness.get_mut().mask = Mask::new(3);
+    let _ = harness.poll_stream();
+}
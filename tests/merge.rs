@@ -0,0 +1,78 @@
+use component_future::merge::{Bias, Either, Merge};
+use component_future::testing::MockStream;
+use futures::future::Future as _;
+use futures::stream::Stream as _;
+
+type Iter<T> = futures::stream::IterOk<std::vec::IntoIter<T>, String>;
+
+fn streams() -> (Iter<u32>, Iter<&'static str>, Iter<bool>) {
+    (
+        futures::stream::iter_ok(vec![1, 2, 3]),
+        futures::stream::iter_ok(vec!["x", "y"]),
+        futures::stream::iter_ok(vec![true, false]),
+    )
+}
+
+#[test]
+fn test_merge_round_robin() {
+    let (a, b, c) = streams();
+    let merged = Merge::new(a, b).and(c);
+    assert_eq!(
+        merged.collect().wait(),
+        Ok(vec![
+            Either::Left(Either::Left(1)),
+            Either::Right(true),
+            Either::Left(Either::Right("x")),
+            Either::Right(false),
+            Either::Left(Either::Left(2)),
+            Either::Left(Either::Right("y")),
+            Either::Left(Either::Left(3)),
+        ])
+    );
+}
+
+#[test]
+fn test_merge_first() {
+    let (a, b, c) = streams();
+    let merged = Merge::new(a, b).bias(Bias::First).and(c);
+    assert_eq!(
+        merged.collect().wait(),
+        Ok(vec![
+            Either::Left(Either::Left(1)),
+            Either::Left(Either::Left(2)),
+            Either::Left(Either::Left(3)),
+            Either::Left(Either::Right("x")),
+            Either::Left(Either::Right("y")),
+            Either::Right(true),
+            Either::Right(false),
+        ])
+    );
+}
+
+#[test]
+fn test_merge_waits_for_all() {
+    let slow = MockStream::<_, ()>::new(vec![
+        futures::Async::NotReady,
+        futures::Async::NotReady,
+        futures::Async::Ready(Some(1)),
+        futures::Async::Ready(None),
+    ]);
+    let fast = futures::stream::iter_ok(vec!["x"]);
+    assert_eq!(
+        Merge::new(slow, fast).collect().wait(),
+        Ok(vec![Either::Right("x"), Either::Left(1)])
+    );
+}
+
+#[test]
+fn test_merge_error() {
+    let failing = MockStream::<u32, _>::from_polls(vec![
+        Ok(futures::Async::NotReady),
+        Err("oops".to_string()),
+    ]);
+    let endless = futures::stream::repeat::<_, String>(1);
+    assert_eq!(
+        Merge::new(failing, endless.take(10)).collect().wait(),
+        Err("oops".to_string())
+    );
+}
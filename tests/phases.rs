@@ -0,0 +1,123 @@
+use component_future::phases::Phases;
+use component_future::testing::{Harness, MockFuture};
+use futures::future::Future as _;
+
+mod run;
+
+type Boxed =
+    Box<dyn futures::future::Future<Item = u32, Error = String> + Send>;
+
+struct TwoFutures {
+    phases: Phases,
+    fut1: Option<Boxed>,
+    fut2: Option<Boxed>,
+    val: u32,
+}
+
+impl TwoFutures {
+    fn new<F1, F2>(fut1: F1, fut2: F2) -> Self
+    where
+        F1: futures::future::Future<Item = u32, Error = String>
+            + Send
+            + 'static,
+        F2: futures::future::Future<Item = u32, Error = String>
+            + Send
+            + 'static,
+    {
+        Self {
+            phases: Phases::new(),
+            fut1: Some(Box::new(fut1)),
+            fut2: Some(Box::new(fut2)),
+            val: 1,
+        }
+    }
+
+    #[allow(clippy::type_complexity)]
+    const PHASES: &'static [&'static [&'static dyn for<'a> Fn(
+        &'a mut Self,
+    )
+        -> component_future::Poll<
+        u32,
+        String,
+    >]] = &[
+        &[&Self::poll_future_1],
+        &[&Self::poll_future_2],
+        &[&Self::poll_return],
+    ];
+
+    fn poll_future_1(&mut self) -> component_future::Poll<u32, String> {
+        if let Some(fut1) = &mut self.fut1 {
+            let val = component_future::try_ready!(fut1.poll());
+            self.val += val;
+            self.fut1.take();
+            Ok(component_future::Async::DidWork)
+        } else {
+            Ok(component_future::Async::NothingToDo)
+        }
+    }
+
+    fn poll_future_2(&mut self) -> component_future::Poll<u32, String> {
+        if let Some(fut2) = &mut self.fut2 {
+            let val = component_future::try_ready!(fut2.poll());
+            self.val *= val;
+            self.fut2.take();
+            Ok(component_future::Async::DidWork)
+        } else {
+            Ok(component_future::Async::NothingToDo)
+        }
+    }
+
+    fn poll_return(&mut self) -> component_future::Poll<u32, String> {
+        Ok(component_future::Async::Ready(self.val))
+    }
+}
+
+impl futures::future::Future for TwoFutures {
+    type Item = u32;
+    type Error = String;
+
+    fn poll(&mut self) -> futures::Poll<Self::Item, Self::Error> {
+        component_future::poll_future_phased(
+            self,
+            |s| &mut s.phases,
+            Self::PHASES,
+        )
+    }
+}
+
+#[test]
+fn test_phases() {
+    let cfut =
+        TwoFutures::new(futures::future::ok(3), futures::future::ok(5));
+    assert_eq!(run::future(cfut), Ok(20));
+}
+
+#[test]
+fn test_phases_in_order() {
+    let fut2 = MockFuture::new(vec![futures::Async::Ready(5)]);
+    let polls = fut2.polls();
+    let mut harness = Harness::new(TwoFutures::new(
+        MockFuture::new(vec![
+            futures::Async::NotReady,
+            futures::Async::Ready(3),
+        ]),
+        fut2,
+    ));
+
+    assert_eq!(harness.poll_future(), Ok(futures::Async::NotReady));
+    assert_eq!(harness.get_ref().phases.current(), 0);
+    assert_eq!(polls.get(), 0);
+
+    assert_eq!(harness.poll_future(), Ok(futures::Async::Ready(20)));
+    assert_eq!(harness.get_ref().phases.current(), 2);
+    assert_eq!(polls.get(), 1);
+}
+
+#[test]
+fn test_phases_error() {
+    let cfut = TwoFutures::new(
+        futures::future::ok(3),
+        futures::future::err("failed".to_string()),
+    );
+    assert_eq!(cfut.wait(), Err("failed".to_string()));
+}
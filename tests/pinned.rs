@@ -0,0 +1,149 @@
+use futures03::channel::oneshot;
+use futures03::stream::StreamExt as _;
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::Context;
+
+#[pin_project::pin_project]
+struct Doubled<F> {
+    #[pin]
+    fut: F,
+    val: Option<u32>,
+}
+
+impl<F> Doubled<F>
+where
+    F: Future<Output = Result<u32, String>> + 'static,
+{
+    #[allow(clippy::type_complexity)]
+    const POLL_FNS:
+        &'static [&'static dyn for<'a, 'b, 'c> Fn(
+            Pin<&'a mut Self>,
+            &'b mut Context<'c>,
+        )
+            -> component_future::Poll<
+            u32,
+            String,
+        >] = &[&Self::poll_fut, &Self::poll_return];
+
+    fn poll_fut(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> component_future::Poll<u32, String> {
+        let this = self.project();
+        if this.val.is_some() {
+            return Ok(component_future::Async::NothingToDo);
+        }
+
+        let val = component_future::try_ready_task!(this.fut.poll(cx));
+        *this.val = Some(val);
+        Ok(component_future::Async::DidWork)
+    }
+
+    fn poll_return(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> component_future::Poll<u32, String> {
+        if let Some(val) = self.val {
+            Ok(component_future::Async::Ready(val * 2))
+        } else {
+            Ok(component_future::Async::NothingToDo)
+        }
+    }
+}
+
+impl<F> Future for Doubled<F>
+where
+    F: Future<Output = Result<u32, String>> + 'static,
+{
+    type Output = Result<u32, String>;
+
+    fn poll(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> std::task::Poll<Self::Output> {
+        component_future::task::poll_future_pinned(self, cx, Self::POLL_FNS)
+    }
+}
+
+#[pin_project::pin_project]
+struct Offsets<S> {
+    #[pin]
+    input: S,
+    offset: u32,
+}
+
+impl<S> Offsets<S>
+where
+    S: futures03::stream::Stream<Item = u32> + 'static,
+{
+    #[allow(clippy::type_complexity)]
+    const POLL_FNS:
+        &'static [&'static dyn for<'a, 'b, 'c> Fn(
+            Pin<&'a mut Self>,
+            &'b mut Context<'c>,
+        )
+            -> component_future::Poll<
+            Option<u32>,
+            String,
+        >] = &[&Self::poll_input];
+
+    fn poll_input(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> component_future::Poll<Option<u32>, String> {
+        let this = self.project();
+        let offset = *this.offset;
+        let val = match this.input.poll_next(cx) {
+            std::task::Poll::Ready(val) => val,
+            std::task::Poll::Pending => {
+                return Ok(component_future::Async::NotReady)
+            }
+        };
+        Ok(component_future::Async::Ready(val.map(|i| i + offset)))
+    }
+}
+
+impl<S> futures03::stream::Stream for Offsets<S>
+where
+    S: futures03::stream::Stream<Item = u32> + 'static,
+{
+    type Item = Result<u32, String>;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        component_future::task::poll_stream_pinned(self, cx, Self::POLL_FNS)
+    }
+}
+
+#[test]
+fn test_pinned_future() {
+    let (tx, rx) = oneshot::channel();
+    // async blocks are !Unpin, so this can only be polled through a Pin
+    let fut = Doubled {
+        fut: async move { rx.await.map_err(|e| e.to_string()) },
+        val: None,
+    };
+    futures03::pin_mut!(fut);
+    let mut cx = Context::from_waker(futures03::task::noop_waker_ref());
+
+    assert_eq!(fut.as_mut().poll(&mut cx), std::task::Poll::Pending);
+    tx.send(21).unwrap();
+    assert_eq!(fut.as_mut().poll(&mut cx), std::task::Poll::Ready(Ok(42)));
+}
+
+#[test]
+fn test_pinned_stream() {
+    let stream = Offsets {
+        input: futures03::stream::iter(vec![1, 2])
+            .chain(futures03::stream::once(async { 3 })),
+        offset: 10,
+    };
+    assert_eq!(
+        futures03::executor::block_on(stream.collect::<Vec<_>>()),
+        vec![Ok(11), Ok(12), Ok(13)]
+    );
+}
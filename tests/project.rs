@@ -0,0 +1,134 @@
+use component_future::project::Project;
+use futures::future::Future as _;
+
+mod run;
+
+type Boxed =
+    Box<dyn futures::future::Future<Item = u32, Error = String> + Send>;
+
+// a reusable component which knows nothing about the outer types it is used
+// in
+struct Slot {
+    fut: Option<Boxed>,
+    val: Option<u32>,
+}
+
+impl Slot {
+    fn new(fut: Boxed) -> Self {
+        Self {
+            fut: Some(fut),
+            val: None,
+        }
+    }
+
+    fn poll<Item>(&mut self) -> component_future::Poll<Item, String> {
+        if let Some(fut) = &mut self.fut {
+            let val = component_future::try_ready!(fut.poll());
+            self.fut.take();
+            self.val = Some(val);
+            Ok(component_future::Async::DidWork)
+        } else {
+            Ok(component_future::Async::NothingToDo)
+        }
+    }
+}
+
+struct Sum {
+    left: Slot,
+    right: Slot,
+}
+
+impl Sum {
+    const POLL_FNS: &'static [&'static dyn component_future::Component<
+        Self,
+        u32,
+        String,
+    >] = &[
+        &Project::new(|sum: &mut Self| &mut sum.left, &Slot::poll),
+        &Project::new(|sum: &mut Self| &mut sum.right, &Slot::poll),
+        &Self::poll_return,
+    ];
+
+    fn poll_return(&mut self) -> component_future::Poll<u32, String> {
+        if let (Some(left), Some(right)) = (self.left.val, self.right.val) {
+            Ok(component_future::Async::Ready(left + right))
+        } else {
+            Ok(component_future::Async::NothingToDo)
+        }
+    }
+}
+
+impl futures::future::Future for Sum {
+    type Item = u32;
+    type Error = String;
+
+    fn poll(&mut self) -> futures::Poll<Self::Item, Self::Error> {
+        component_future::poll_future(self, Self::POLL_FNS)
+    }
+}
+
+struct Countdown {
+    delay: Slot,
+    remaining: u32,
+}
+
+impl Countdown {
+    const POLL_FNS: &'static [&'static dyn component_future::Component<
+        Self,
+        Option<u32>,
+        String,
+    >] = &[
+        &Project::new(
+            |countdown: &mut Self| &mut countdown.delay,
+            &Slot::poll,
+        ),
+        &Self::poll_output,
+    ];
+
+    fn poll_output(&mut self) -> component_future::Poll<Option<u32>, String> {
+        if let Some(start) = self.delay.val {
+            if self.remaining == 0 {
+                return Ok(component_future::Async::Ready(None));
+            }
+            self.remaining -= 1;
+            Ok(component_future::Async::Ready(Some(start + self.remaining)))
+        } else {
+            Ok(component_future::Async::NothingToDo)
+        }
+    }
+}
+
+impl futures::stream::Stream for Countdown {
+    type Item = u32;
+    type Error = String;
+
+    fn poll(&mut self) -> futures::Poll<Option<Self::Item>, Self::Error> {
+        component_future::poll_stream(self, Self::POLL_FNS)
+    }
+}
+
+#[test]
+fn test_project_future() {
+    let fut = Sum {
+        left: Slot::new(Box::new(futures::future::ok(3))),
+        right: Slot::new(Box::new(futures::future::lazy(|| {
+            futures::future::ok(4)
+        }))),
+    };
+    assert_eq!(run::future(fut), Ok(7));
+
+    let fut = Sum {
+        left: Slot::new(Box::new(futures::future::ok(3))),
+        right: Slot::new(Box::new(futures::future::err("oops".to_string()))),
+    };
+    assert_eq!(run::future(fut), Err("oops".to_string()));
+}
+
+#[test]
+fn test_project_stream() {
+    let stream = Countdown {
+        delay: Slot::new(Box::new(futures::future::ok(10))),
+        remaining: 3,
+    };
+    assert_eq!(run::stream(stream), Ok(vec![12, 11, 10]));
+}
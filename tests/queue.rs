@@ -0,0 +1,111 @@
+use component_future::queue::Queue;
+use component_future::testing::MockStream;
+use futures::stream::Stream as _;
+
+use std::collections::VecDeque;
+
+mod run;
+
+struct Decoder {
+    input: MockStream<Vec<u32>, String>,
+    pending: VecDeque<u32>,
+    done: bool,
+}
+
+impl Decoder {
+    #[allow(clippy::type_complexity)]
+    const POLL_FNS:
+        &'static [&'static dyn for<'a, 'b> Fn(
+            &'a mut Self,
+            &'b mut Queue<u32>,
+        )
+            -> component_future::Poll<
+            Option<u32>,
+            String,
+        >] = &[&Self::poll_input, &Self::poll_frames, &Self::poll_end];
+
+    fn poll_input(
+        &mut self,
+        _queue: &mut Queue<u32>,
+    ) -> component_future::Poll<Option<u32>, String> {
+        if self.done || !self.pending.is_empty() {
+            return Ok(component_future::Async::NothingToDo);
+        }
+
+        if let Some(chunk) = component_future::try_ready!(self.input.poll()) {
+            self.pending.extend(chunk);
+        } else {
+            self.done = true;
+        }
+        Ok(component_future::Async::DidWork)
+    }
+
+    fn poll_frames(
+        &mut self,
+        queue: &mut Queue<u32>,
+    ) -> component_future::Poll<Option<u32>, String> {
+        let mut did_work = false;
+        while !queue.is_full() {
+            if let Some(frame) = self.pending.pop_front() {
+                queue.emit(frame);
+                did_work = true;
+            } else {
+                break;
+            }
+        }
+
+        if did_work {
+            Ok(component_future::Async::DidWork)
+        } else {
+            Ok(component_future::Async::NothingToDo)
+        }
+    }
+
+    fn poll_end(
+        &mut self,
+        _queue: &mut Queue<u32>,
+    ) -> component_future::Poll<Option<u32>, String> {
+        if self.done && self.pending.is_empty() {
+            Ok(component_future::Async::Ready(None))
+        } else {
+            Ok(component_future::Async::NothingToDo)
+        }
+    }
+}
+
+struct Frames {
+    decoder: Decoder,
+    queue: Queue<u32>,
+}
+
+impl futures::stream::Stream for Frames {
+    type Item = u32;
+    type Error = String;
+
+    fn poll(&mut self) -> futures::Poll<Option<Self::Item>, Self::Error> {
+        component_future::poll_stream_queued(
+            &mut self.decoder,
+            &mut self.queue,
+            Decoder::POLL_FNS,
+        )
+    }
+}
+
+#[test]
+fn test_queue() {
+    let stream = Frames {
+        decoder: Decoder {
+            input: MockStream::new(vec![
+                futures::Async::Ready(Some(vec![1, 2, 3])),
+                futures::Async::NotReady,
+                futures::Async::Ready(Some(vec![])),
+                futures::Async::Ready(Some(vec![4, 5, 6, 7, 8])),
+                futures::Async::Ready(None),
+            ]),
+            pending: VecDeque::new(),
+            done: false,
+        },
+        queue: Queue::with_capacity(2),
+    };
+    assert_eq!(run::stream(stream), Ok(vec![1, 2, 3, 4, 5, 6, 7, 8]));
+}
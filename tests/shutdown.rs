@@ -0,0 +1,111 @@
+use component_future::shutdown::{Role, Signal};
+use component_future::testing::{Harness, MockFuture};
+use futures::future::Future as _;
+use futures::stream::Stream as _;
+
+enum State {
+    Reading,
+    Processing(MockFuture<u32, String>),
+}
+
+struct Server {
+    input: futures::sync::mpsc::UnboundedReceiver<u32>,
+    state: State,
+    shutdown: Signal,
+}
+
+impl Server {
+    #[allow(clippy::type_complexity)]
+    const POLL_FNS: &'static [(
+        Role,
+        &'static dyn component_future::Component<Self, Option<u32>, String>,
+    )] = &[
+        (Role::Intake, &Self::poll_input),
+        (Role::Drain, &Self::poll_state),
+    ];
+
+    fn poll_input(&mut self) -> component_future::Poll<Option<u32>, String> {
+        if let State::Processing(..) = self.state {
+            return Ok(component_future::Async::NothingToDo);
+        }
+
+        let input = component_future::try_ready!(self
+            .input
+            .poll()
+            .map_err(|()| "input failed".to_string()));
+        if let Some(input) = input {
+            self.state = State::Processing(MockFuture::new(vec![
+                futures::Async::NotReady,
+                futures::Async::NotReady,
+                futures::Async::Ready(input * 10),
+            ]));
+            Ok(component_future::Async::DidWork)
+        } else {
+            Ok(component_future::Async::Ready(None))
+        }
+    }
+
+    fn poll_state(&mut self) -> component_future::Poll<Option<u32>, String> {
+        if let State::Processing(fut) = &mut self.state {
+            let output = component_future::try_ready!(fut.poll());
+            self.state = State::Reading;
+            Ok(component_future::Async::Ready(Some(output)))
+        } else {
+            Ok(component_future::Async::NothingToDo)
+        }
+    }
+}
+
+impl futures::stream::Stream for Server {
+    type Item = u32;
+    type Error = String;
+
+    fn poll(&mut self) -> futures::Poll<Option<Self::Item>, Self::Error> {
+        component_future::poll_stream_shutdown(
+            self,
+            |server| &server.shutdown,
+            Self::POLL_FNS,
+        )
+    }
+}
+
+#[test]
+fn test_shutdown_drains() {
+    let (tx, rx) = futures::sync::mpsc::unbounded();
+    let shutdown = Signal::new();
+    let mut harness = Harness::new(Server {
+        input: rx,
+        state: State::Reading,
+        shutdown: shutdown.clone(),
+    });
+
+    tx.unbounded_send(1).unwrap();
+    assert_eq!(harness.poll_stream(), Ok(futures::Async::NotReady));
+
+    tx.unbounded_send(2).unwrap();
+    shutdown.trigger();
+    assert!(harness.is_notified());
+
+    // the request which was already accepted is still processed
+    assert_eq!(harness.poll_stream(), Ok(futures::Async::Ready(Some(10))));
+    // but the next one isn't accepted
+    assert_eq!(harness.poll_stream(), Ok(futures::Async::Ready(None)));
+}
+
+#[test]
+fn test_shutdown_idle() {
+    let (_tx, rx) = futures::sync::mpsc::unbounded();
+    let shutdown = Signal::new();
+    let mut harness = Harness::new(Server {
+        input: rx,
+        state: State::Reading,
+        shutdown: shutdown.clone(),
+    });
+
+    assert_eq!(harness.poll_stream(), Ok(futures::Async::NotReady));
+    assert!(!harness.is_notified());
+
+    shutdown.trigger();
+    assert!(harness.is_notified());
+    assert_eq!(harness.poll_stream(), Ok(futures::Async::Ready(None)));
+}
@@ -0,0 +1,119 @@
+use futures::future::Future as _;
+
+mod run;
+
+#[derive(Debug, PartialEq, Eq)]
+struct InputEvent(u32);
+#[derive(Debug, PartialEq, Eq)]
+struct OutputEvent(u32);
+
+type Output = Box<
+    dyn futures::future::Future<Item = OutputEvent, Error = String> + Send,
+>;
+
+impl InputEvent {
+    fn into_output_event(self) -> Output {
+        let Self(i) = self;
+        if i == 0 {
+            Box::new(futures::future::err("zero".to_string()))
+        } else {
+            Box::new(futures::future::ok(OutputEvent(i)))
+        }
+    }
+}
+
+component_future::state_machine! {
+    enum State for Stream.state -> Poll<Option<OutputEvent>, String> {
+        Reading,
+        Processing(Output) => |stream, event| {
+            stream.processed += 1;
+            (State::Reading, component_future::Async::Ready(Some(event)))
+        },
+    }
+}
+
+struct Stream {
+    input: Box<
+        dyn futures::stream::Stream<Item = InputEvent, Error = String> + Send,
+    >,
+    state: State,
+    processed: usize,
+}
+
+impl Stream {
+    fn new(vals: Vec<u32>) -> Self {
+        Self {
+            input: Box::new(futures::stream::iter_ok(
+                vals.into_iter().map(InputEvent),
+            )),
+            state: State::Reading,
+            processed: 0,
+        }
+    }
+
+    #[allow(clippy::type_complexity)]
+    const POLL_FNS: &'static [&'static dyn component_future::Component<
+        Self,
+        Option<OutputEvent>,
+        String,
+    >] = &[&Self::poll_input, &State::poll];
+
+    fn poll_input(
+        &mut self,
+    ) -> component_future::Poll<Option<OutputEvent>, String> {
+        if let State::Processing(..) = self.state {
+            return Ok(component_future::Async::NothingToDo);
+        }
+
+        if let Some(input_event) =
+            component_future::try_ready!(self.input.poll())
+        {
+            self.state = State::Processing(input_event.into_output_event());
+            Ok(component_future::Async::DidWork)
+        } else {
+            Ok(component_future::Async::Ready(None))
+        }
+    }
+}
+
+impl futures::stream::Stream for Stream {
+    type Item = OutputEvent;
+    type Error = String;
+
+    fn poll(&mut self) -> futures::Poll<Option<Self::Item>, Self::Error> {
+        component_future::poll_stream(self, Self::POLL_FNS)
+    }
+}
+
+#[test]
+fn test_state_machine() {
+    let vals = vec![5, 3, 1, 35];
+    let events = run::stream(Stream::new(vals.clone()));
+    assert_eq!(events, Ok(vals.into_iter().map(OutputEvent).collect()));
+}
+
+#[test]
+fn test_state_machine_idle() {
+    let mut stream = Stream::new(vec![]);
+    assert_eq!(
+        State::poll(&mut stream),
+        Ok(component_future::Async::NothingToDo)
+    );
+
+    stream.state = State::Processing(InputEvent(2).into_output_event());
+    assert_eq!(
+        State::poll(&mut stream),
+        Ok(component_future::Async::Ready(Some(OutputEvent(2))))
+    );
+    assert!(matches!(stream.state, State::Reading));
+    assert_eq!(stream.processed, 1);
+}
+
+#[test]
+fn test_state_machine_error() {
+    let stream = Stream::new(vec![1, 0, 2]);
+    assert_eq!(
+        futures::stream::Stream::collect(stream).wait(),
+        Err("zero".to_string())
+    );
+}
@@ -0,0 +1,136 @@
+use component_future::supervise::{Policy, State, Supervised};
+
+mod run;
+
+struct Reconnecting {
+    upstream:
+        Box<dyn futures::future::Future<Item = u32, Error = String> + Send>,
+    upstream_state: State,
+    failures: u32,
+    max_restarts: usize,
+}
+
+impl Reconnecting {
+    fn new(failures: u32, max_restarts: usize) -> Self {
+        let mut s = Self {
+            upstream: Box::new(futures::future::ok(0)),
+            upstream_state: State::new(),
+            failures,
+            max_restarts,
+        };
+        s.connect();
+        s
+    }
+
+    fn connect(&mut self) {
+        if self.failures > 0 {
+            self.failures -= 1;
+            self.upstream = Box::new(futures::future::err(format!(
+                "connection failed ({} left)",
+                self.failures
+            )));
+        } else {
+            self.upstream = Box::new(futures::future::ok(
+                self.upstream_state.restarts() as u32,
+            ));
+        }
+    }
+
+    fn poll_upstream(&mut self) -> component_future::Poll<u32, String> {
+        let val = component_future::try_ready!(self.upstream.poll());
+        Ok(component_future::Async::Ready(val))
+    }
+}
+
+impl futures::future::Future for Reconnecting {
+    type Item = u32;
+    type Error = String;
+
+    fn poll(&mut self) -> futures::Poll<Self::Item, Self::Error> {
+        let max_restarts = self.max_restarts;
+        component_future::poll_future(
+            self,
+            &[Supervised::new(
+                &Self::poll_upstream,
+                |s: &mut Self| &mut s.upstream_state,
+                Policy::Restart {
+                    reset: &Self::connect,
+                    max_restarts,
+                },
+            )],
+        )
+    }
+}
+
+#[test]
+fn test_restart() {
+    let fut = Reconnecting::new(2, 3);
+    assert_eq!(run::future(fut), Ok(2));
+}
+
+#[test]
+fn test_restart_limit() {
+    let fut = Reconnecting::new(4, 3);
+    assert_eq!(
+        run::future(fut),
+        Err("connection failed (0 left)".to_string())
+    );
+}
+
+struct Retiring {
+    flaky: Option<
+        Box<dyn futures::future::Future<Item = u32, Error = String> + Send>,
+    >,
+    flaky_state: State,
+    input:
+        Box<dyn futures::stream::Stream<Item = u32, Error = String> + Send>,
+}
+
+impl Retiring {
+    const POLL_FNS: &'static [&'static dyn component_future::Component<
+        Self,
+        Option<u32>,
+        String,
+    >] = &[
+        &Supervised::new(
+            &Self::poll_flaky,
+            |s: &mut Self| &mut s.flaky_state,
+            Policy::Retire,
+        ),
+        &Self::poll_input,
+    ];
+
+    fn poll_flaky(&mut self) -> component_future::Poll<Option<u32>, String> {
+        if let Some(flaky) = &mut self.flaky {
+            let val = component_future::try_ready!(flaky.poll());
+            self.flaky.take();
+            Ok(component_future::Async::Ready(Some(val)))
+        } else {
+            Ok(component_future::Async::NothingToDo)
+        }
+    }
+
+    fn poll_input(&mut self) -> component_future::Poll<Option<u32>, String> {
+        let val = component_future::try_ready!(self.input.poll());
+        Ok(component_future::Async::Ready(val))
+    }
+}
+
+impl futures::stream::Stream for Retiring {
+    type Item = u32;
+    type Error = String;
+
+    fn poll(&mut self) -> futures::Poll<Option<Self::Item>, Self::Error> {
+        component_future::poll_stream(self, Self::POLL_FNS)
+    }
+}
+
+#[test]
+fn test_retire() {
+    let stream = Retiring {
+        flaky: Some(Box::new(futures::future::err("oops".to_string()))),
+        flaky_state: State::new(),
+        input: Box::new(futures::stream::iter_ok(vec![1, 2, 3])),
+    };
+    assert_eq!(run::stream(stream), Ok(vec![1, 2, 3]));
+}
@@ -0,0 +1,178 @@
+use futures03::channel::oneshot;
+use futures03::future::FutureExt as _;
+use futures03::stream::StreamExt as _;
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::Context;
+
+struct TwoFutures {
+    fut1: Option<oneshot::Receiver<u32>>,
+    fut2: Option<oneshot::Receiver<u32>>,
+    val: u32,
+}
+
+impl TwoFutures {
+    #[allow(clippy::type_complexity)]
+    const POLL_FNS:
+        &'static [&'static dyn for<'a, 'b, 'c> Fn(
+            &'a mut Self,
+            &'b mut Context<'c>,
+        )
+            -> component_future::Poll<
+            u32,
+            oneshot::Canceled,
+        >] = &[
+        &Self::poll_future_1,
+        &Self::poll_future_2,
+        &Self::poll_return,
+    ];
+
+    fn poll_future_1(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> component_future::Poll<u32, oneshot::Canceled> {
+        if let Some(fut1) = &mut self.fut1 {
+            let val = component_future::try_ready_task!(fut1.poll_unpin(cx));
+            self.val += val;
+            self.fut1.take();
+            Ok(component_future::Async::DidWork)
+        } else {
+            Ok(component_future::Async::NothingToDo)
+        }
+    }
+
+    fn poll_future_2(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> component_future::Poll<u32, oneshot::Canceled> {
+        if self.fut1.is_some() {
+            return Ok(component_future::Async::NothingToDo);
+        }
+
+        if let Some(fut2) = &mut self.fut2 {
+            let val = component_future::try_ready_task!(fut2.poll_unpin(cx));
+            self.val *= val;
+            self.fut2.take();
+            Ok(component_future::Async::DidWork)
+        } else {
+            Ok(component_future::Async::NothingToDo)
+        }
+    }
+
+    fn poll_return(
+        &mut self,
+        _cx: &mut Context<'_>,
+    ) -> component_future::Poll<u32, oneshot::Canceled> {
+        if self.fut1.is_some() || self.fut2.is_some() {
+            return Ok(component_future::Async::NothingToDo);
+        }
+
+        Ok(component_future::Async::Ready(self.val))
+    }
+}
+
+impl Future for TwoFutures {
+    type Output = Result<u32, oneshot::Canceled>;
+
+    fn poll(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> std::task::Poll<Self::Output> {
+        component_future::task::poll_future(
+            self.get_mut(),
+            cx,
+            Self::POLL_FNS,
+        )
+    }
+}
+
+#[test]
+fn test_task_future() {
+    let (tx1, rx1) = oneshot::channel();
+    let (tx2, rx2) = oneshot::channel();
+    let mut fut = TwoFutures {
+        fut1: Some(rx1),
+        fut2: Some(rx2),
+        val: 1,
+    };
+    let mut cx = Context::from_waker(futures03::task::noop_waker_ref());
+
+    assert_eq!(fut.poll_unpin(&mut cx), std::task::Poll::Pending);
+    tx1.send(3).unwrap();
+    assert_eq!(fut.poll_unpin(&mut cx), std::task::Poll::Pending);
+    tx2.send(5).unwrap();
+    assert_eq!(fut.poll_unpin(&mut cx), std::task::Poll::Ready(Ok(20)));
+}
+
+#[test]
+fn test_task_future_error() {
+    let (tx1, rx1) = oneshot::channel::<u32>();
+    let (_tx2, rx2) = oneshot::channel();
+    drop(tx1);
+    let fut = TwoFutures {
+        fut1: Some(rx1),
+        fut2: Some(rx2),
+        val: 1,
+    };
+    assert_eq!(futures03::executor::block_on(fut), Err(oneshot::Canceled));
+}
+
+struct Numbers {
+    input: futures03::stream::Iter<std::vec::IntoIter<Result<u32, String>>>,
+}
+
+impl Numbers {
+    #[allow(clippy::type_complexity)]
+    const POLL_FNS:
+        &'static [&'static dyn for<'a, 'b, 'c> Fn(
+            &'a mut Self,
+            &'b mut Context<'c>,
+        )
+            -> component_future::Poll<
+            Option<u32>,
+            String,
+        >] = &[&Self::poll_input];
+
+    fn poll_input(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> component_future::Poll<Option<u32>, String> {
+        let val = component_future::try_ready_task!(self
+            .input
+            .poll_next_unpin(cx)
+            .map(Option::transpose));
+        Ok(component_future::Async::Ready(val.map(|i| i * 2)))
+    }
+}
+
+impl futures03::stream::Stream for Numbers {
+    type Item = Result<u32, String>;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        component_future::task::poll_stream(
+            self.get_mut(),
+            cx,
+            Self::POLL_FNS,
+        )
+    }
+}
+
+#[test]
+fn test_task_stream() {
+    let stream = Numbers {
+        input: futures03::stream::iter(vec![
+            Ok(1),
+            Ok(2),
+            Err("oops".to_string()),
+            Ok(3),
+        ]),
+    };
+    assert_eq!(
+        futures03::executor::block_on(stream.collect::<Vec<_>>()),
+        vec![Ok(2), Ok(4), Err("oops".to_string()), Ok(6)]
+    );
+}
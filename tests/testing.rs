@@ -0,0 +1,123 @@
+use component_future::testing::{Harness, MockFuture, MockStream};
+use futures::future::Future as _;
+use futures::stream::Stream as _;
+
+mod run;
+
+struct Sum {
+    fut: Option<MockFuture<u32, String>>,
+    stream: MockStream<u32, String>,
+    sum: u32,
+}
+
+impl Sum {
+    #[allow(clippy::type_complexity)]
+    const POLL_FNS:
+        &'static [&'static dyn for<'a> Fn(
+            &'a mut Self,
+        )
+            -> component_future::Poll<
+            u32,
+            String,
+        >] = &[&Self::poll_fut, &Self::poll_stream];
+
+    fn poll_fut(&mut self) -> component_future::Poll<u32, String> {
+        if let Some(fut) = &mut self.fut {
+            let val = component_future::try_ready!(fut.poll());
+            self.sum += val;
+            self.fut.take();
+            Ok(component_future::Async::DidWork)
+        } else {
+            Ok(component_future::Async::NothingToDo)
+        }
+    }
+
+    fn poll_stream(&mut self) -> component_future::Poll<u32, String> {
+        if let Some(val) = component_future::try_ready!(self.stream.poll()) {
+            self.sum += val;
+            Ok(component_future::Async::DidWork)
+        } else if self.fut.is_none() {
+            Ok(component_future::Async::Ready(self.sum))
+        } else {
+            Ok(component_future::Async::NothingToDo)
+        }
+    }
+}
+
+impl futures::future::Future for Sum {
+    type Item = u32;
+    type Error = String;
+
+    fn poll(&mut self) -> futures::Poll<Self::Item, Self::Error> {
+        component_future::poll_future(self, Self::POLL_FNS)
+    }
+}
+
+#[test]
+fn test_mock_not_ready() {
+    let fut = MockFuture::new(vec![
+        futures::Async::NotReady,
+        futures::Async::NotReady,
+        futures::Async::Ready(10),
+    ]);
+    let fut_polls = fut.polls();
+    let stream = MockStream::new(vec![
+        futures::Async::Ready(Some(1)),
+        futures::Async::NotReady,
+        futures::Async::Ready(Some(2)),
+        futures::Async::Ready(None),
+    ]);
+    let stream_polls = stream.polls();
+
+    let mut harness = Harness::new(Sum {
+        fut: Some(fut),
+        stream,
+        sum: 0,
+    });
+
+    assert_eq!(harness.poll_future(), Ok(futures::Async::NotReady));
+    assert!(harness.is_notified());
+    assert_eq!(fut_polls.get(), 2);
+    assert_eq!(stream_polls.get(), 2);
+    assert_eq!(harness.get_ref().sum, 1);
+
+    assert_eq!(harness.poll_future(), Ok(futures::Async::Ready(13)));
+    assert_eq!(fut_polls.get(), 3);
+    assert_eq!(stream_polls.get(), 4);
+}
+
+#[test]
+fn test_mock_error() {
+    let fut = MockFuture::from_polls(vec![
+        Ok(futures::Async::NotReady),
+        Err("failed".to_string()),
+    ]);
+    let stream = MockStream::new(vec![futures::Async::NotReady]);
+    let mut harness = Harness::new(Sum {
+        fut: Some(fut),
+        stream,
+        sum: 0,
+    });
+
+    assert_eq!(harness.poll_future(), Ok(futures::Async::NotReady));
+    assert_eq!(harness.poll_future(), Err("failed".to_string()));
+}
+
+#[test]
+fn test_mock_executor() {
+    let fut = Sum {
+        fut: Some(MockFuture::new(vec![
+            futures::Async::NotReady,
+            futures::Async::Ready(5),
+        ])),
+        stream: MockStream::new(vec![
+            futures::Async::NotReady,
+            futures::Async::NotReady,
+            futures::Async::NotReady,
+            futures::Async::Ready(Some(7)),
+            futures::Async::Ready(None),
+        ]),
+        sum: 0,
+    };
+    assert_eq!(run::future(fut), Ok(12));
+}
@@ -0,0 +1,259 @@
+use component_future::testing::{Harness, MockFuture, VirtualClock};
+use component_future::timeout::{State, ThreadTimer, Timeout};
+use futures::future::Future as _;
+
+use std::time::Duration;
+
+type Response = Box<dyn futures::future::Future<Item = u32, Error = String>>;
+
+struct Request {
+    response: Response,
+    response_timeout: State<VirtualClock>,
+    retries: u32,
+}
+
+impl Request {
+    fn new(response: Response, clock: &VirtualClock) -> Self {
+        Self {
+            response,
+            response_timeout: State::new(clock.clone()),
+            retries: 0,
+        }
+    }
+
+    fn poll_response(&mut self) -> component_future::Poll<u32, String> {
+        let val = component_future::try_ready!(self.response.poll());
+        Ok(component_future::Async::Ready(val))
+    }
+
+    fn retry(&mut self) -> component_future::Poll<u32, String> {
+        if self.retries >= 1 {
+            return Err("timed out".to_string());
+        }
+        self.retries += 1;
+        self.response = Box::new(MockFuture::new(vec![
+            futures::Async::NotReady,
+            futures::Async::Ready(self.retries),
+        ]));
+        Ok(component_future::Async::DidWork)
+    }
+}
+
+impl futures::future::Future for Request {
+    type Item = u32;
+    type Error = String;
+
+    fn poll(&mut self) -> futures::Poll<Self::Item, Self::Error> {
+        component_future::poll_future(
+            self,
+            &[Timeout::new(
+                &Self::poll_response,
+                |s: &mut Self| &mut s.response_timeout,
+                Duration::from_secs(5),
+                &Self::retry,
+            )],
+        )
+    }
+}
+
+// never ready, and never notifies the task
+fn pending() -> Response {
+    Box::new(futures::future::empty())
+}
+
+#[test]
+fn test_timeout_retry() {
+    let clock = VirtualClock::new();
+    let mut harness = Harness::new(Request::new(pending(), &clock));
+
+    assert_eq!(harness.poll_future(), Ok(futures::Async::NotReady));
+    clock.advance(Duration::from_secs(4));
+    assert!(!harness.is_notified());
+
+    clock.advance(Duration::from_secs(1));
+    assert!(harness.is_notified());
+    assert_eq!(harness.poll_future(), Ok(futures::Async::NotReady));
+    assert_eq!(harness.get_ref().response_timeout.timeouts(), 1);
+
+    assert_eq!(harness.poll_future(), Ok(futures::Async::Ready(1)));
+}
+
+#[test]
+fn test_timeout_error() {
+    let clock = VirtualClock::new();
+    let mut harness = Harness::new(Request::new(pending(), &clock));
+    harness.get_mut().retries = 1;
+
+    assert_eq!(harness.poll_future(), Ok(futures::Async::NotReady));
+    clock.advance(Duration::from_secs(3));
+    assert!(!harness.is_notified());
+    assert_eq!(harness.poll_future(), Ok(futures::Async::NotReady));
+    clock.advance(Duration::from_secs(3));
+    assert!(harness.is_notified());
+    assert_eq!(harness.poll_future(), Err("timed out".to_string()));
+}
+
+#[test]
+fn test_timeout_reset() {
+    let clock = VirtualClock::new();
+    let mut harness = Harness::new(Request::new(
+        Box::new(MockFuture::new(vec![
+            futures::Async::NotReady,
+            futures::Async::NotReady,
+            futures::Async::Ready(7),
+        ])),
+        &clock,
+    ));
+
+    assert_eq!(harness.poll_future(), Ok(futures::Async::NotReady));
+    assert_eq!(
+        harness.get_ref().response_timeout.deadline(),
+        Some(Duration::from_secs(5))
+    );
+    clock.advance(Duration::from_secs(2));
+    assert_eq!(harness.poll_future(), Ok(futures::Async::NotReady));
+    // the deadline doesn't move while the component is still waiting
+    assert_eq!(
+        harness.get_ref().response_timeout.deadline(),
+        Some(Duration::from_secs(5))
+    );
+    assert_eq!(harness.poll_future(), Ok(futures::Async::Ready(7)));
+    assert_eq!(harness.get_ref().response_timeout.deadline(), None);
+    assert_eq!(harness.get_ref().response_timeout.timeouts(), 0);
+}
+
+// counts the number of times a wakeup was requested
+#[derive(Clone)]
+struct CountingTimer {
+    clock: VirtualClock,
+    calls: std::rc::Rc<std::cell::Cell<usize>>,
+}
+
+impl component_future::timeout::Timer for CountingTimer {
+    type Instant = Duration;
+
+    fn now(&self) -> Self::Instant {
+        self.clock.now()
+    }
+
+    fn notify_at(&self, deadline: Self::Instant) {
+        self.calls.set(self.calls.get() + 1);
+        self.clock.notify_at(deadline);
+    }
+}
+
+struct Counted {
+    response: Response,
+    response_timeout: State<CountingTimer>,
+}
+
+impl futures::future::Future for Counted {
+    type Item = u32;
+    type Error = String;
+
+    fn poll(&mut self) -> futures::Poll<Self::Item, Self::Error> {
+        component_future::poll_future(
+            self,
+            &[Timeout::new(
+                &|s: &mut Self| {
+                    let val = component_future::try_ready!(s.response.poll());
+                    Ok(component_future::Async::Ready(val))
+                },
+                |s: &mut Self| &mut s.response_timeout,
+                Duration::from_secs(5),
+                &|_| Err("timed out".to_string()),
+            )],
+        )
+    }
+}
+
+#[test]
+fn test_timeout_arms_once() {
+    let timer = CountingTimer {
+        clock: VirtualClock::new(),
+        calls: std::rc::Rc::default(),
+    };
+    let mut harness = Harness::new(Counted {
+        response: pending(),
+        response_timeout: State::new(timer.clone()),
+    });
+
+    for _ in 0..10 {
+        assert_eq!(harness.poll_future(), Ok(futures::Async::NotReady));
+    }
+    assert_eq!(timer.calls.get(), 1);
+
+    timer.clock.advance(Duration::from_secs(5));
+    assert!(harness.is_notified());
+    assert_eq!(harness.poll_future(), Err("timed out".to_string()));
+}
+
+struct Threaded {
+    response: Response,
+    progress: bool,
+    response_timeout: State<ThreadTimer>,
+}
+
+impl futures::future::Future for Threaded {
+    type Item = u32;
+    type Error = String;
+
+    fn poll(&mut self) -> futures::Poll<Self::Item, Self::Error> {
+        component_future::poll_future(
+            self,
+            &[Timeout::new(
+                &|s: &mut Self| {
+                    if s.progress {
+                        s.progress = false;
+                        return Ok(component_future::Async::DidWork);
+                    }
+                    let val = component_future::try_ready!(s.response.poll());
+                    Ok(component_future::Async::Ready(val))
+                },
+                |s: &mut Self| &mut s.response_timeout,
+                Duration::from_millis(200),
+                &|_| Err("timed out".to_string()),
+            )],
+        )
+    }
+}
+
+#[test]
+fn test_thread_timer() {
+    let mut harness = Harness::new(Threaded {
+        response: pending(),
+        progress: false,
+        response_timeout: State::new(ThreadTimer::new()),
+    });
+
+    for _ in 0..100 {
+        assert_eq!(harness.poll_future(), Ok(futures::Async::NotReady));
+    }
+    std::thread::sleep(Duration::from_millis(400));
+    assert!(harness.is_notified());
+    assert_eq!(harness.poll_future(), Err("timed out".to_string()));
+}
+
+#[test]
+fn test_thread_timer_restart() {
+    let mut harness = Harness::new(Threaded {
+        response: pending(),
+        progress: false,
+        response_timeout: State::new(ThreadTimer::new()),
+    });
+
+    assert_eq!(harness.poll_future(), Ok(futures::Async::NotReady));
+    std::thread::sleep(Duration::from_millis(100));
+
+    // restarts the wait, so the new deadline is after the first one
+    harness.get_mut().progress = true;
+    assert_eq!(harness.poll_future(), Ok(futures::Async::NotReady));
+
+    // past the first deadline, but not the second
+    std::thread::sleep(Duration::from_millis(150));
+    assert_eq!(harness.poll_future(), Ok(futures::Async::NotReady));
+
+    std::thread::sleep(Duration::from_millis(300));
+    assert!(harness.is_notified());
+    assert_eq!(harness.poll_future(), Err("timed out".to_string()));
+}
@@ -0,0 +1,72 @@
+use component_future::testing::{Harness, MockFuture};
+use futures::future::Future as _;
+
+struct Waiting {
+    fut: MockFuture<u32, String>,
+    lie: bool,
+}
+
+impl Waiting {
+    #[allow(clippy::type_complexity)]
+    const POLL_FNS:
+        &'static [&'static dyn for<'a> Fn(
+            &'a mut Self,
+        )
+            -> component_future::Poll<
+            u32,
+            String,
+        >] = &[&Self::poll_lie, &Self::poll_fut];
+
+    const DRIVER: component_future::Driver =
+        component_future::Driver::new().check_wakeups(true);
+
+    fn poll_lie(&mut self) -> component_future::Poll<u32, String> {
+        if self.lie {
+            Ok(component_future::Async::NotReady)
+        } else {
+            Ok(component_future::Async::NothingToDo)
+        }
+    }
+
+    fn poll_fut(&mut self) -> component_future::Poll<u32, String> {
+        if self.lie {
+            return Ok(component_future::Async::NothingToDo);
+        }
+
+        let val = component_future::try_ready!(self.fut.poll());
+        Ok(component_future::Async::Ready(val))
+    }
+}
+
+impl futures::future::Future for Waiting {
+    type Item = u32;
+    type Error = String;
+
+    fn poll(&mut self) -> futures::Poll<Self::Item, Self::Error> {
+        Self::DRIVER.poll_future(self, Self::POLL_FNS)
+    }
+}
+
+#[test]
+fn test_registered() {
+    let mut harness = Harness::new(Waiting {
+        fut: MockFuture::new(vec![
+            futures::Async::NotReady,
+            futures::Async::Ready(1),
+        ]),
+        lie: false,
+    });
+    assert_eq!(harness.poll_future(), Ok(futures::Async::NotReady));
+    assert!(harness.is_notified());
+    assert_eq!(harness.poll_future(), Ok(futures::Async::Ready(1)));
+}
+
+#[test]
+#[should_panic(expected = "without registering for a wakeup")]
+fn test_lost_wakeup() {
+    let mut harness = Harness::new(Waiting {
+        fut: MockFuture::new(vec![]),
+        lie: true,
+    });
+    let _ = harness.poll_future();
+}
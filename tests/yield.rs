@@ -0,0 +1,86 @@
+use component_future::testing::Harness;
+
+use std::collections::VecDeque;
+
+mod run;
+
+struct Batched {
+    buffer: VecDeque<u32>,
+    processed_since_yield: usize,
+    sum: u32,
+}
+
+impl Batched {
+    const BATCH_SIZE: usize = 2;
+
+    #[allow(clippy::type_complexity)]
+    const POLL_FNS:
+        &'static [&'static dyn for<'a> Fn(
+            &'a mut Self,
+        )
+            -> component_future::Poll<
+            u32,
+            String,
+        >] = &[&Self::poll_buffer, &Self::poll_return];
+
+    fn poll_buffer(&mut self) -> component_future::Poll<u32, String> {
+        if self.processed_since_yield >= Self::BATCH_SIZE {
+            self.processed_since_yield = 0;
+            return Ok(component_future::Async::Yield);
+        }
+
+        if let Some(val) = self.buffer.pop_front() {
+            self.sum += val;
+            self.processed_since_yield += 1;
+            Ok(component_future::Async::DidWork)
+        } else {
+            Ok(component_future::Async::NothingToDo)
+        }
+    }
+
+    fn poll_return(&mut self) -> component_future::Poll<u32, String> {
+        if self.buffer.is_empty() {
+            Ok(component_future::Async::Ready(self.sum))
+        } else {
+            Ok(component_future::Async::NothingToDo)
+        }
+    }
+}
+
+impl futures::future::Future for Batched {
+    type Item = u32;
+    type Error = String;
+
+    fn poll(&mut self) -> futures::Poll<Self::Item, Self::Error> {
+        component_future::poll_future(self, Self::POLL_FNS)
+    }
+}
+
+#[test]
+fn test_yield() {
+    let mut harness = Harness::new(Batched {
+        buffer: vec![1, 2, 3, 4, 5].into_iter().collect(),
+        processed_since_yield: 0,
+        sum: 0,
+    });
+
+    assert_eq!(harness.poll_future(), Ok(futures::Async::NotReady));
+    assert!(harness.is_notified());
+    assert_eq!(harness.get_ref().sum, 3);
+
+    assert_eq!(harness.poll_future(), Ok(futures::Async::NotReady));
+    assert!(harness.is_notified());
+    assert_eq!(harness.get_ref().sum, 10);
+
+    assert_eq!(harness.poll_future(), Ok(futures::Async::Ready(15)));
+}
+
+#[test]
+fn test_yield_executor() {
+    let fut = Batched {
+        buffer: (1..=100).collect(),
+        processed_since_yield: 0,
+        sum: 0,
+    };
+    assert_eq!(run::future(fut), Ok(5050));
+}